@@ -0,0 +1,277 @@
+#![allow(dead_code)]
+use crate::{
+    animation_clip::{Animatable, ChannelValue},
+    animation_sampling_system::{self, AnimationTime},
+    components::*,
+    ecs::{prelude::*, systems::SubWorld},
+    math::{UnitQuaternion, Vector3},
+};
+use std::collections::{HashMap, HashSet};
+
+/// The blended local transform of a single entity. Properties that no layer animates are `None`
+/// and left untouched.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct LocalPose {
+    pub translation: Option<Vector3<f32>>,
+    pub rotation: Option<UnitQuaternion<f32>>,
+    pub scale: Option<f32>,
+    pub non_uniform_scale: Option<Vector3<f32>>,
+}
+
+impl LocalPose {
+    /// Blends a sampled value on top of the pose. The bottom-most override layer animating a
+    /// property sets it outright, as there is nothing below it to blend from.
+    pub fn blend(&mut self, value: ChannelValue, weight: f32, blend: LayerBlend) {
+        match value {
+            ChannelValue::Translation(value) => {
+                self.translation = Some(match (self.translation, blend) {
+                    (None, LayerBlend::Override) => value,
+                    (Some(current), LayerBlend::Override) => current.lerp(&value, weight),
+                    (current, LayerBlend::Additive) => {
+                        current.unwrap_or_else(Vector3::zeros) + value * weight
+                    }
+                })
+            }
+            ChannelValue::Rotation(value) => {
+                self.rotation = Some(match (self.rotation, blend) {
+                    (None, LayerBlend::Override) => value,
+                    (Some(current), LayerBlend::Override) => {
+                        UnitQuaternion::interpolate_linear(&current, &value, weight)
+                    }
+                    (current, LayerBlend::Additive) => {
+                        current.unwrap_or_else(UnitQuaternion::identity)
+                            * UnitQuaternion::interpolate_linear(
+                                &UnitQuaternion::identity(),
+                                &value,
+                                weight,
+                            )
+                    }
+                })
+            }
+            ChannelValue::Scale(value) => {
+                self.scale = Some(match (self.scale, blend) {
+                    (None, LayerBlend::Override) => value,
+                    (Some(current), LayerBlend::Override) => current + (value - current) * weight,
+                    (current, LayerBlend::Additive) => current.unwrap_or(1.0) * value.powf(weight),
+                })
+            }
+            ChannelValue::NonUniformScale(value) => {
+                self.non_uniform_scale = Some(match (self.non_uniform_scale, blend) {
+                    (None, LayerBlend::Override) => value,
+                    (Some(current), LayerBlend::Override) => current.lerp(&value, weight),
+                    (current, LayerBlend::Additive) => current
+                        .unwrap_or_else(|| Vector3::repeat(1.0))
+                        .component_mul(&value.map(|scale| scale.powf(weight))),
+                })
+            }
+        }
+    }
+
+    /// The animated properties of the pose.
+    pub fn values(&self) -> impl Iterator<Item = ChannelValue> {
+        let values = [
+            self.translation.map(ChannelValue::Translation),
+            self.rotation.map(ChannelValue::Rotation),
+            self.scale.map(ChannelValue::Scale),
+            self.non_uniform_scale.map(ChannelValue::NonUniformScale),
+        ];
+        (0..values.len()).filter_map(move |i| values[i])
+    }
+}
+
+pub fn build(_: &mut World, resources: &mut Resources) -> Box<dyn Schedulable> {
+    if !resources.contains::<AnimationTime>() {
+        resources.insert(AnimationTime::default());
+    }
+
+    SystemBuilder::<()>::new("AnimationBlendSystem")
+        .read_resource::<AnimationTime>()
+        // Animated roots with layers
+        .with_query(<Write<AnimationLayers>>::query())
+        .read_component::<Parent>()
+        .read_component::<Children>()
+        .read_component::<Name>()
+        .read_component::<NameIndex>()
+        .write_component::<Translation>()
+        .write_component::<Rotation>()
+        .write_component::<Scale>()
+        .write_component::<NonUniformScale>()
+        .build(move |commands, world, time, query| {
+            let mut roots = Vec::new();
+            for (entity, mut layers) in query.iter_entities_mut(world) {
+                for layer in layers.iter_mut() {
+                    layer.player.advance(time.delta_seconds);
+                }
+                roots.push((entity, layers.clone()));
+            }
+
+            for (root, layers) in roots {
+                for (entity, pose) in blend_layers(world, root, &layers) {
+                    for value in pose.values() {
+                        animation_sampling_system::write_value(world, commands, entity, value);
+                    }
+                }
+            }
+        })
+}
+
+/// Samples and blends every layer playing under `root`, bottom layer first.
+pub fn blend_layers(
+    world: &SubWorld,
+    root: Entity,
+    layers: &AnimationLayers,
+) -> HashMap<Entity, LocalPose> {
+    let mut poses = HashMap::<Entity, LocalPose>::new();
+    for layer in layers.iter() {
+        if layer.weight <= 0.0 {
+            continue;
+        }
+
+        let mask = layer
+            .mask
+            .as_ref()
+            .map(|mask| masked_entities(world, root, mask));
+        let values = animation_sampling_system::sample_clip(
+            world,
+            root,
+            &layer.player.clip,
+            layer.player.time,
+        );
+        for (entity, value) in values {
+            if mask.as_ref().map_or(true, |mask| mask.contains(&entity)) {
+                poses
+                    .entry(entity)
+                    .or_default()
+                    .blend(value, layer.weight.min(1.0), layer.blend);
+            }
+        }
+    }
+    poses
+}
+
+/// All entities in the subtrees rooted at the mask's paths, including the roots themselves.
+pub fn masked_entities(world: &SubWorld, root: Entity, mask: &LayerMask) -> HashSet<Entity> {
+    let mut entities = HashSet::new();
+    let mut stack = mask
+        .roots
+        .iter()
+        .filter_map(|path| animation_sampling_system::resolve_path(world, root, path))
+        .collect::<Vec<_>>();
+    while let Some(entity) = stack.pop() {
+        if entities.insert(entity) {
+            if let Some(children) = world.get_component::<Children>(entity) {
+                stack.extend(children.iter().copied());
+            }
+        }
+    }
+    entities
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        animation_clip::{AnimationClip, Channel, ChannelTrack, Interpolation, Keyframe, Track},
+        hierarchy_maintenance_system,
+    };
+    use std::sync::Arc;
+
+    fn translation_channel(path: &str, x: f32) -> Channel {
+        let translation = Vector3::new(x, 0.0, 0.0);
+        Channel::new(
+            path,
+            ChannelTrack::Translation(Track::new(
+                Interpolation::Linear,
+                vec![
+                    Keyframe::new(0.0, translation),
+                    Keyframe::new(1.0, translation),
+                ],
+            )),
+        )
+    }
+
+    fn player(channels: Vec<Channel>) -> AnimationPlayer {
+        AnimationPlayer::new(Arc::new(AnimationClip::new(channels)))
+    }
+
+    #[test]
+    fn did_blend_layers() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut resources = Resources::default();
+        let mut world = Universe::new().create_world();
+        let mut systems = hierarchy_maintenance_system::build(&mut world, &mut resources);
+        systems.push(build(&mut world, &mut resources));
+
+        // root -> spine -> arm, and root -> leg
+        let root = *world
+            .insert((), vec![(LocalToWorld::identity(),)])
+            .first()
+            .unwrap();
+        let mut insert_child = |name: &str, parent: Entity| {
+            *world
+                .insert(
+                    (),
+                    vec![(
+                        Name::from(name),
+                        Translation::identity(),
+                        Parent(parent),
+                        LocalToParent::identity(),
+                        LocalToWorld::identity(),
+                    )],
+                )
+                .first()
+                .unwrap()
+        };
+        let spine = insert_child("spine", root);
+        let arm = insert_child("arm", spine);
+        let leg = insert_child("leg", root);
+
+        let layers = AnimationLayers::with(&[
+            AnimationLayer::new(
+                player(vec![
+                    translation_channel("spine", 1.0),
+                    translation_channel("spine/arm", 1.0),
+                    translation_channel("leg", 1.0),
+                ]),
+                1.0,
+            ),
+            // Half way towards 3.0, on the upper body only.
+            AnimationLayer::new(
+                player(vec![
+                    translation_channel("spine/arm", 3.0),
+                    translation_channel("leg", 3.0),
+                ]),
+                0.5,
+            )
+            .with_mask(LayerMask::new(&["spine"])),
+            // Adds half of 2.0.
+            AnimationLayer::new(player(vec![translation_channel("leg", 2.0)]), 0.5).additive(),
+        ]);
+        world.add_component(root, layers).unwrap();
+
+        // The first run builds the hierarchy, the second blends.
+        for _ in 0..2 {
+            for system in systems.iter_mut() {
+                system.run(&mut world, &mut resources);
+                system
+                    .command_buffer_mut(world.id())
+                    .unwrap()
+                    .write(&mut world);
+            }
+        }
+
+        assert_eq!(
+            *world.get_component::<Translation>(spine).unwrap(),
+            Translation::new(1.0, 0.0, 0.0)
+        );
+        assert_eq!(
+            *world.get_component::<Translation>(arm).unwrap(),
+            Translation::new(2.0, 0.0, 0.0)
+        );
+        assert_eq!(
+            *world.get_component::<Translation>(leg).unwrap(),
+            Translation::new(2.0, 0.0, 0.0)
+        );
+    }
+}
@@ -0,0 +1,323 @@
+use crate::math::{Quaternion, UnitQuaternion, Vector3};
+use std::fmt::Debug;
+
+/// How values are interpolated between two keyframes of a `Track`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Interpolation {
+    /// Holds the value of the previous keyframe until the next one is reached.
+    Step,
+    /// Linear interpolation, spherical for rotations.
+    Linear,
+    /// Cubic Hermite spline using the keyframes' `in_tangent` and `out_tangent`.
+    CubicSpline,
+}
+
+impl Default for Interpolation {
+    fn default() -> Self {
+        Interpolation::Linear
+    }
+}
+
+/// A value that can be stored in the keyframes of a `Track`.
+pub trait Animatable: Copy + PartialEq + Debug + Send + Sync + 'static {
+    /// The type of the tangents used by `Interpolation::CubicSpline`.
+    type Tangent: Copy + PartialEq + Debug + Send + Sync + 'static;
+
+    fn zero_tangent() -> Self::Tangent;
+
+    fn interpolate_linear(from: &Self, to: &Self, t: f32) -> Self;
+
+    /// Cubic Hermite interpolation between `from` and `to`, where the tangents are expressed per
+    /// second and `duration` is the time between both keyframes.
+    fn interpolate_cubic(
+        from: &Self,
+        from_out_tangent: &Self::Tangent,
+        to: &Self,
+        to_in_tangent: &Self::Tangent,
+        t: f32,
+        duration: f32,
+    ) -> Self;
+}
+
+/// The Hermite basis functions at `t`.
+#[inline(always)]
+fn hermite(t: f32) -> [f32; 4] {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    [
+        2.0 * t3 - 3.0 * t2 + 1.0,
+        t3 - 2.0 * t2 + t,
+        -2.0 * t3 + 3.0 * t2,
+        t3 - t2,
+    ]
+}
+
+impl Animatable for f32 {
+    type Tangent = f32;
+
+    fn zero_tangent() -> f32 {
+        0.0
+    }
+
+    fn interpolate_linear(from: &f32, to: &f32, t: f32) -> f32 {
+        from + (to - from) * t
+    }
+
+    fn interpolate_cubic(
+        from: &f32,
+        from_out_tangent: &f32,
+        to: &f32,
+        to_in_tangent: &f32,
+        t: f32,
+        duration: f32,
+    ) -> f32 {
+        let [h00, h10, h01, h11] = hermite(t);
+        h00 * from + h10 * duration * from_out_tangent + h01 * to + h11 * duration * to_in_tangent
+    }
+}
+
+impl Animatable for Vector3<f32> {
+    type Tangent = Vector3<f32>;
+
+    fn zero_tangent() -> Vector3<f32> {
+        Vector3::zeros()
+    }
+
+    fn interpolate_linear(from: &Vector3<f32>, to: &Vector3<f32>, t: f32) -> Vector3<f32> {
+        from.lerp(to, t)
+    }
+
+    fn interpolate_cubic(
+        from: &Vector3<f32>,
+        from_out_tangent: &Vector3<f32>,
+        to: &Vector3<f32>,
+        to_in_tangent: &Vector3<f32>,
+        t: f32,
+        duration: f32,
+    ) -> Vector3<f32> {
+        let [h00, h10, h01, h11] = hermite(t);
+        from * h00
+            + from_out_tangent * (h10 * duration)
+            + to * h01
+            + to_in_tangent * (h11 * duration)
+    }
+}
+
+impl Animatable for UnitQuaternion<f32> {
+    type Tangent = Quaternion<f32>;
+
+    fn zero_tangent() -> Quaternion<f32> {
+        Quaternion::new(0.0, 0.0, 0.0, 0.0)
+    }
+
+    fn interpolate_linear(
+        from: &UnitQuaternion<f32>,
+        to: &UnitQuaternion<f32>,
+        t: f32,
+    ) -> UnitQuaternion<f32> {
+        // Take the shortest path, falling back to nlerp when both rotations are (nearly) opposite.
+        let to = if from.coords.dot(&to.coords) < 0.0 {
+            UnitQuaternion::new_unchecked(-to.into_inner())
+        } else {
+            *to
+        };
+        from.try_slerp(&to, t, f32::EPSILON)
+            .unwrap_or_else(|| from.nlerp(&to, t))
+    }
+
+    fn interpolate_cubic(
+        from: &UnitQuaternion<f32>,
+        from_out_tangent: &Quaternion<f32>,
+        to: &UnitQuaternion<f32>,
+        to_in_tangent: &Quaternion<f32>,
+        t: f32,
+        duration: f32,
+    ) -> UnitQuaternion<f32> {
+        let [h00, h10, h01, h11] = hermite(t);
+        let coords = from.coords * h00
+            + from_out_tangent.coords * (h10 * duration)
+            + to.coords * h01
+            + to_in_tangent.coords * (h11 * duration);
+        UnitQuaternion::new_normalize(Quaternion::from(coords))
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Keyframe<T: Animatable> {
+    /// Time of the keyframe in seconds from the start of the clip.
+    pub time: f32,
+    pub value: T,
+    /// Only used by `Interpolation::CubicSpline`.
+    pub in_tangent: T::Tangent,
+    /// Only used by `Interpolation::CubicSpline`.
+    pub out_tangent: T::Tangent,
+}
+
+impl<T: Animatable> Keyframe<T> {
+    #[inline(always)]
+    pub fn new(time: f32, value: T) -> Self {
+        Self {
+            time,
+            value,
+            in_tangent: T::zero_tangent(),
+            out_tangent: T::zero_tangent(),
+        }
+    }
+
+    #[inline(always)]
+    pub fn with_tangents(mut self, in_tangent: T::Tangent, out_tangent: T::Tangent) -> Self {
+        self.in_tangent = in_tangent;
+        self.out_tangent = out_tangent;
+        self
+    }
+}
+
+/// Keyframes of a single value, sorted by time.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Track<T: Animatable> {
+    pub interpolation: Interpolation,
+    pub keyframes: Vec<Keyframe<T>>,
+}
+
+impl<T: Animatable> Track<T> {
+    /// Creates a track, sorting the keyframes by time. Keyframes with a NaN time (from broken asset
+    /// data) can't be ordered and are dropped.
+    pub fn new(interpolation: Interpolation, mut keyframes: Vec<Keyframe<T>>) -> Self {
+        let count = keyframes.len();
+        keyframes.retain(|keyframe| !keyframe.time.is_nan());
+        if keyframes.len() != count {
+            log::warn!(
+                "Dropped {} keyframes with a NaN time",
+                count - keyframes.len()
+            );
+        }
+        keyframes.sort_by(|a, b| a.time.total_cmp(&b.time));
+        Self {
+            interpolation,
+            keyframes,
+        }
+    }
+
+    /// Time of the last keyframe.
+    pub fn duration(&self) -> f32 {
+        self.keyframes
+            .last()
+            .map(|keyframe| keyframe.time)
+            .unwrap_or(0.0)
+    }
+
+    /// Samples the track at `time`, holding the first and last values outside of the keyframes.
+    /// Returns `None` if the track has no keyframes.
+    pub fn sample(&self, time: f32) -> Option<T> {
+        let first = self.keyframes.first()?;
+        let last = self.keyframes.last()?;
+        if time.is_nan() || time <= first.time {
+            return Some(first.value);
+        }
+        if time >= last.time {
+            return Some(last.value);
+        }
+
+        // Index of the first keyframe after `time`, which is at least 1. The fallback only matters
+        // for keyframes (with a NaN time) pushed after `Track::new`.
+        let next = self
+            .keyframes
+            .iter()
+            .position(|keyframe| keyframe.time > time)
+            .unwrap_or(self.keyframes.len() - 1)
+            .max(1);
+        let (from, to) = (&self.keyframes[next - 1], &self.keyframes[next]);
+        let duration = to.time - from.time;
+        let t = (time - from.time) / duration;
+
+        Some(match self.interpolation {
+            Interpolation::Step => from.value,
+            Interpolation::Linear => T::interpolate_linear(&from.value, &to.value, t),
+            Interpolation::CubicSpline => T::interpolate_cubic(
+                &from.value,
+                &from.out_tangent,
+                &to.value,
+                &to.in_tangent,
+                t,
+                duration,
+            ),
+        })
+    }
+}
+
+/// The component a `Channel` animates.
+#[derive(Debug, PartialEq, Clone)]
+pub enum ChannelTrack {
+    Translation(Track<Vector3<f32>>),
+    Rotation(Track<UnitQuaternion<f32>>),
+    Scale(Track<f32>),
+    NonUniformScale(Track<Vector3<f32>>),
+}
+
+impl ChannelTrack {
+    pub fn duration(&self) -> f32 {
+        match self {
+            ChannelTrack::Translation(track) => track.duration(),
+            ChannelTrack::Rotation(track) => track.duration(),
+            ChannelTrack::Scale(track) => track.duration(),
+            ChannelTrack::NonUniformScale(track) => track.duration(),
+        }
+    }
+
+    pub fn sample(&self, time: f32) -> Option<ChannelValue> {
+        match self {
+            ChannelTrack::Translation(track) => track.sample(time).map(ChannelValue::Translation),
+            ChannelTrack::Rotation(track) => track.sample(time).map(ChannelValue::Rotation),
+            ChannelTrack::Scale(track) => track.sample(time).map(ChannelValue::Scale),
+            ChannelTrack::NonUniformScale(track) => {
+                track.sample(time).map(ChannelValue::NonUniformScale)
+            }
+        }
+    }
+}
+
+/// A value sampled from a `ChannelTrack`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ChannelValue {
+    Translation(Vector3<f32>),
+    Rotation(UnitQuaternion<f32>),
+    Scale(f32),
+    NonUniformScale(Vector3<f32>),
+}
+
+/// A track applied to the entity found at `path`, a `/` separated list of `Name`s relative to the
+/// animated root. An empty path targets the root itself.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Channel {
+    pub path: String,
+    pub track: ChannelTrack,
+}
+
+impl Channel {
+    #[inline(always)]
+    pub fn new<S: Into<String>>(path: S, track: ChannelTrack) -> Self {
+        Self {
+            path: path.into(),
+            track,
+        }
+    }
+}
+
+/// An authored animation, shared between `AnimationPlayer`s.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct AnimationClip {
+    /// Length of the clip in seconds.
+    pub duration: f32,
+    pub channels: Vec<Channel>,
+}
+
+impl AnimationClip {
+    /// Creates a clip that lasts until the last keyframe of any channel.
+    pub fn new(channels: Vec<Channel>) -> Self {
+        let duration = channels
+            .iter()
+            .map(|channel| channel.track.duration())
+            .fold(0.0, f32::max);
+        Self { duration, channels }
+    }
+}
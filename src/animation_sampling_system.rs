@@ -0,0 +1,237 @@
+#![allow(dead_code)]
+use crate::{
+    animation_clip::{AnimationClip, ChannelValue},
+    components::*,
+    ecs::{prelude::*, systems::SubWorld},
+    hierarchy_path,
+    math::Translation3,
+};
+
+/// Time elapsed since the `AnimationSamplingSystem` last ran, which the application updates every
+/// frame. The `LookAtSystem` also uses it to limit how fast entities turn.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct AnimationTime {
+    pub delta_seconds: f32,
+}
+
+pub fn build(_: &mut World, resources: &mut Resources) -> Box<dyn Schedulable> {
+    if !resources.contains::<AnimationTime>() {
+        resources.insert(AnimationTime::default());
+    }
+
+    SystemBuilder::<()>::new("AnimationSamplingSystem")
+        .read_resource::<AnimationTime>()
+        // Animated roots
+        .with_query(<Write<AnimationPlayer>>::query())
+        .read_component::<Parent>()
+        .read_component::<Children>()
+        .read_component::<Name>()
+        .read_component::<NameIndex>()
+        .write_component::<Translation>()
+        .write_component::<Rotation>()
+        .write_component::<Scale>()
+        .write_component::<NonUniformScale>()
+        .build(move |commands, world, time, query| {
+            let mut players = Vec::new();
+            for (entity, mut player) in query.iter_entities_mut(world) {
+                if player.playing {
+                    player.advance(time.delta_seconds);
+                    players.push((entity, player.clip.clone(), player.time));
+                }
+            }
+
+            for (root, clip, time) in players {
+                for (entity, value) in sample_clip(world, root, &clip, time) {
+                    write_value(world, commands, entity, value);
+                }
+            }
+        })
+}
+
+/// Samples every channel of `clip` at `time`, resolving the channel paths relative to `root` (see
+/// `resolve_path`). Channels whose target can't be found are skipped. The calling system needs read
+/// access to `Parent`, `Children`, `Name` and `NameIndex`.
+pub fn sample_clip(
+    world: &SubWorld,
+    root: Entity,
+    clip: &AnimationClip,
+    time: f32,
+) -> Vec<(Entity, ChannelValue)> {
+    clip.channels
+        .iter()
+        .filter_map(|channel| {
+            let entity = match resolve_path(world, root, &channel.path) {
+                Some(entity) => entity,
+                None => {
+                    log::trace!(
+                        "Animation target {:?} not found under {}",
+                        channel.path,
+                        root
+                    );
+                    return None;
+                }
+            };
+            channel.track.sample(time).map(|value| (entity, value))
+        })
+        .collect()
+}
+
+/// Resolves a path relative to `root` through its `NameIndex`, if it has one, rather than walking
+/// `Children` for every channel of every frame. Paths missing from the index (which may not have
+/// been rebuilt yet this frame) fall back to `hierarchy_path::find_by_path`.
+pub fn resolve_path(world: &SubWorld, root: Entity, path: &str) -> Option<Entity> {
+    world
+        .get_component::<NameIndex>(root)
+        .and_then(|index| index.get(path))
+        .or_else(|| hierarchy_path::find_by_path(world, root, path))
+}
+
+/// Writes a sampled value into the matching component of `entity`, adding the component if it is
+/// missing.
+pub fn write_value(
+    world: &mut SubWorld,
+    commands: &mut CommandBuffer,
+    entity: Entity,
+    value: ChannelValue,
+) {
+    match value {
+        ChannelValue::Translation(value) => {
+            let translation = Translation(Translation3::from(value));
+            match world.get_component_mut::<Translation>(entity) {
+                Some(mut current) => *current = translation,
+                None => commands.add_component(entity, translation),
+            }
+        }
+        ChannelValue::Rotation(value) => {
+            let rotation = Rotation(value);
+            match world.get_component_mut::<Rotation>(entity) {
+                Some(mut current) => *current = rotation,
+                None => commands.add_component(entity, rotation),
+            }
+        }
+        ChannelValue::Scale(value) => {
+            let scale = Scale(value);
+            match world.get_component_mut::<Scale>(entity) {
+                Some(mut current) => *current = scale,
+                None => commands.add_component(entity, scale),
+            }
+        }
+        ChannelValue::NonUniformScale(value) => {
+            let non_uniform_scale = NonUniformScale(value);
+            match world.get_component_mut::<NonUniformScale>(entity) {
+                Some(mut current) => *current = non_uniform_scale,
+                None => commands.add_component(entity, non_uniform_scale),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        animation_clip::{Channel, ChannelTrack, Interpolation, Keyframe, Track},
+        hierarchy_maintenance_system,
+        math::{UnitQuaternion, Vector3},
+    };
+    use std::{f32::consts::PI, sync::Arc};
+
+    #[test]
+    fn did_sample_clip() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut resources = Resources::default();
+        let mut world = Universe::new().create_world();
+        let mut systems = hierarchy_maintenance_system::build(&mut world, &mut resources);
+        systems.push(build(&mut world, &mut resources));
+
+        let clip = AnimationClip::new(vec![
+            Channel::new(
+                "",
+                ChannelTrack::Translation(Track::new(
+                    Interpolation::Linear,
+                    vec![
+                        Keyframe::new(0.0, Vector3::new(0.0, 0.0, 0.0)),
+                        Keyframe::new(2.0, Vector3::new(2.0, 4.0, 0.0)),
+                    ],
+                )),
+            ),
+            Channel::new(
+                "arm/hand",
+                ChannelTrack::Rotation(Track::new(
+                    Interpolation::Linear,
+                    vec![
+                        Keyframe::new(0.0, UnitQuaternion::identity()),
+                        Keyframe::new(2.0, UnitQuaternion::from_euler_angles(0.0, PI, 0.0)),
+                    ],
+                )),
+            ),
+            Channel::new(
+                "arm",
+                ChannelTrack::Scale(Track::new(
+                    Interpolation::Step,
+                    vec![Keyframe::new(0.0, 1.0), Keyframe::new(1.0, 3.0)],
+                )),
+            ),
+        ]);
+
+        let root = *world
+            .insert(
+                (),
+                vec![(
+                    AnimationPlayer::new(Arc::new(clip)),
+                    Translation::identity(),
+                    LocalToWorld::identity(),
+                )],
+            )
+            .first()
+            .unwrap();
+        let arm = *world
+            .insert(
+                (),
+                vec![(
+                    Name::from("arm"),
+                    Parent(root),
+                    LocalToParent::identity(),
+                    LocalToWorld::identity(),
+                )],
+            )
+            .first()
+            .unwrap();
+        let hand = *world
+            .insert(
+                (),
+                vec![(
+                    Name::from("hand"),
+                    Rotation::identity(),
+                    Parent(arm),
+                    LocalToParent::identity(),
+                    LocalToWorld::identity(),
+                )],
+            )
+            .first()
+            .unwrap();
+
+        // The first run builds the hierarchy, the second samples the clip at 1 second.
+        for delta_seconds in [0.0, 1.0].iter() {
+            resources.get_mut::<AnimationTime>().unwrap().delta_seconds = *delta_seconds;
+            for system in systems.iter_mut() {
+                system.run(&mut world, &mut resources);
+                system
+                    .command_buffer_mut(world.id())
+                    .unwrap()
+                    .write(&mut world);
+            }
+        }
+
+        assert_eq!(
+            *world.get_component::<Translation>(root).unwrap(),
+            Translation::new(1.0, 2.0, 0.0)
+        );
+        assert_eq!(*world.get_component::<Scale>(arm).unwrap(), Scale(3.0));
+
+        let expected = UnitQuaternion::from_euler_angles(0.0, PI / 2.0, 0.0);
+        let rotation = world.get_component::<Rotation>(hand).unwrap();
+        assert!(rotation.angle_to(&expected) < 1.0e-4);
+    }
+}
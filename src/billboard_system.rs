@@ -0,0 +1,158 @@
+#![allow(dead_code)]
+use crate::{
+    components::*,
+    ecs::{prelude::*, systems::SubWorld},
+    local_to_world_propagate_system,
+    math::{Matrix4, UnitQuaternion, Vector3},
+};
+
+pub fn build(_: &mut World, _: &mut Resources) -> Box<dyn Schedulable> {
+    let builder = SystemBuilder::<()>::new("BillboardSystem");
+    local_to_world_propagate_system::declare_repropagate_access(builder)
+        // Entities facing a camera
+        .with_query(
+            <Read<Billboard>>::query()
+                .filter(component::<LocalToWorld>() & !component::<StaticTransform>()),
+        )
+        .build(move |_commands, world, _resource, query| {
+            let billboards = query
+                .iter_entities(world)
+                .map(|(entity, billboard)| (entity, *billboard))
+                .collect::<Vec<_>>();
+
+            for (entity, billboard) in billboards {
+                if let Some(local_to_world) = face_camera(world, entity, &billboard) {
+                    local_to_world_propagate_system::repropagate(world, entity, local_to_world);
+                }
+            }
+        })
+}
+
+fn face_camera(world: &SubWorld, entity: Entity, billboard: &Billboard) -> Option<LocalToWorld> {
+    let local_to_world = *world.get_component::<LocalToWorld>(entity)?;
+    let camera = match world.get_component::<LocalToWorld>(billboard.camera) {
+        Some(camera) => *camera,
+        None => {
+            log::warn!(
+                "Entity {} is a billboard for a camera without a LocalToWorld",
+                entity
+            );
+            return None;
+        }
+    };
+
+    let position = local_to_world.position();
+    let to_camera = camera.position() - position;
+    let camera_rotation = camera.rotation();
+
+    let rotation = match billboard.mode {
+        BillboardMode::Spherical => {
+            if to_camera.norm_squared() < f32::EPSILON {
+                return None;
+            }
+            // Seen from straight above or below, keep the billboard's up along the camera's
+            // forward axis instead.
+            let up = camera_rotation * Vector3::y();
+            let up = if up.cross(&to_camera.normalize()).norm_squared() < f32::EPSILON {
+                camera_rotation * Vector3::z()
+            } else {
+                up
+            };
+            UnitQuaternion::face_towards(&to_camera, &up)
+        }
+        BillboardMode::Cylindrical(axis) => {
+            let to_camera = to_camera - axis.into_inner() * axis.dot(&to_camera);
+            if to_camera.norm_squared() < f32::EPSILON {
+                return None;
+            }
+            UnitQuaternion::face_towards(&to_camera, &axis.into_inner())
+        }
+        BillboardMode::ScreenAligned => camera_rotation,
+    };
+
+    let mut matrix =
+        rotation.to_homogeneous() * Matrix4::new_nonuniform_scaling(&local_to_world.scale());
+    matrix.set_column(3, &position.to_homogeneous());
+    Some(LocalToWorld(matrix))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{math::Point3, transform_system_bundle};
+
+    #[test]
+    fn did_face_camera() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut resources = Resources::default();
+        let mut world = Universe::new().create_world();
+        let mut systems = transform_system_bundle::build(&mut world, &mut resources);
+
+        let camera = *world
+            .insert(
+                (),
+                vec![(Translation::new(10.0, 5.0, 0.0), LocalToWorld::identity())],
+            )
+            .first()
+            .unwrap();
+
+        let billboards = world.insert(
+            (),
+            vec![
+                (
+                    Billboard::spherical(camera),
+                    Translation::new(0.0, 0.0, 0.0),
+                    Scale(2.0),
+                    LocalToWorld::identity(),
+                ),
+                (
+                    Billboard::cylindrical(camera, Vector3::y_axis()),
+                    Translation::new(0.0, 0.0, 0.0),
+                    Scale(2.0),
+                    LocalToWorld::identity(),
+                ),
+            ],
+        );
+        let (spherical, cylindrical) = (billboards[0], billboards[1]);
+
+        // A child of the cylindrical billboard, one unit along its +Z axis.
+        let child = *world
+            .insert(
+                (),
+                vec![(
+                    Translation::new(0.0, 0.0, 1.0),
+                    Parent(cylindrical),
+                    LocalToParent::identity(),
+                    LocalToWorld::identity(),
+                )],
+            )
+            .first()
+            .unwrap();
+
+        for _ in 0..2 {
+            for system in systems.iter_mut() {
+                system.run(&mut world, &mut resources);
+                system
+                    .command_buffer_mut(world.id())
+                    .unwrap()
+                    .write(&mut world);
+            }
+        }
+
+        let local_to_world = *world.get_component::<LocalToWorld>(spherical).unwrap();
+        let forward = local_to_world.0.column(2).xyz();
+        assert!((forward - Vector3::new(10.0, 5.0, 0.0).normalize() * 2.0).norm() < 1e-4);
+
+        let local_to_world = *world.get_component::<LocalToWorld>(cylindrical).unwrap();
+        let forward = local_to_world.0.column(2).xyz();
+        assert!((forward - Vector3::new(2.0, 0.0, 0.0)).norm() < 1e-4);
+
+        // Children follow the billboard.
+        let child_position = world
+            .get_component::<LocalToWorld>(child)
+            .unwrap()
+            .position();
+        assert!((child_position - Point3::new(2.0, 0.0, 0.0)).norm() < 1e-4);
+    }
+}
@@ -0,0 +1,73 @@
+use crate::components::AnimationPlayer;
+use shrinkwraprs::Shrinkwrap;
+
+/// How a layer combines with the layers below it.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum LayerBlend {
+    /// Blends from the pose below towards the layer's pose by the layer's weight.
+    Override,
+    /// The layer's clip holds offsets (translation added, rotation and scale multiplied) which are
+    /// applied on top of the pose below, scaled by the layer's weight.
+    Additive,
+}
+
+/// Restricts a layer to the subtrees rooted at the given paths (relative to the animated root, see
+/// `Channel::path`).
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct LayerMask {
+    pub roots: Vec<String>,
+}
+
+impl LayerMask {
+    pub fn new(roots: &[&str]) -> Self {
+        Self {
+            roots: roots.iter().map(|root| (*root).to_owned()).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AnimationLayer {
+    pub player: AnimationPlayer,
+    pub weight: f32,
+    pub blend: LayerBlend,
+    /// The layer affects the whole hierarchy if `None`.
+    pub mask: Option<LayerMask>,
+}
+
+impl AnimationLayer {
+    #[inline(always)]
+    pub fn new(player: AnimationPlayer, weight: f32) -> Self {
+        Self {
+            player,
+            weight,
+            blend: LayerBlend::Override,
+            mask: None,
+        }
+    }
+
+    #[inline(always)]
+    pub fn additive(mut self) -> Self {
+        self.blend = LayerBlend::Additive;
+        self
+    }
+
+    #[inline(always)]
+    pub fn with_mask(mut self, mask: LayerMask) -> Self {
+        self.mask = Some(mask);
+        self
+    }
+}
+
+/// A stack of animation layers playing on the entity it is added to and its descendants, bottom
+/// layer first. The `AnimationBlendSystem` advances every layer, blends their poses and writes the
+/// resulting `Translation`, `Rotation`, `Scale` and `NonUniformScale` of each animated entity.
+#[derive(Shrinkwrap, Debug, Clone, Default)]
+#[shrinkwrap(mutable)]
+pub struct AnimationLayers(pub Vec<AnimationLayer>);
+
+impl AnimationLayers {
+    pub fn with(layers: &[AnimationLayer]) -> Self {
+        Self(layers.to_vec())
+    }
+}
@@ -0,0 +1,61 @@
+use crate::animation_clip::AnimationClip;
+use std::sync::Arc;
+
+/// Plays an `AnimationClip` on the entity it is added to and its descendants. The
+/// `AnimationSamplingSystem` advances `time` and writes the sampled `Translation`, `Rotation`,
+/// `Scale` and `NonUniformScale` of every channel's target before `LocalToParent` is computed.
+#[derive(Debug, Clone)]
+pub struct AnimationPlayer {
+    pub clip: Arc<AnimationClip>,
+    /// Playback position in seconds.
+    pub time: f32,
+    /// Playback speed multiplier, negative values play the clip backwards.
+    pub speed: f32,
+    /// Wraps around at either end of the clip instead of stopping there.
+    pub looping: bool,
+    pub playing: bool,
+}
+
+impl AnimationPlayer {
+    #[inline(always)]
+    pub fn new(clip: Arc<AnimationClip>) -> Self {
+        Self {
+            clip,
+            time: 0.0,
+            speed: 1.0,
+            looping: false,
+            playing: true,
+        }
+    }
+
+    #[inline(always)]
+    pub fn looping(mut self) -> Self {
+        self.looping = true;
+        self
+    }
+
+    #[inline(always)]
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Moves the playback position forward by `delta_seconds` (scaled by `speed`), wrapping around
+    /// or stopping at the ends of the clip.
+    pub fn advance(&mut self, delta_seconds: f32) {
+        if !self.playing {
+            return;
+        }
+
+        let duration = self.clip.duration;
+        self.time += delta_seconds * self.speed;
+        if self.looping && duration > 0.0 {
+            self.time = self.time.rem_euclid(duration);
+        } else {
+            let finished = (self.speed > 0.0 && self.time >= duration)
+                || (self.speed < 0.0 && self.time <= 0.0);
+            self.time = self.time.max(0.0).min(duration);
+            self.playing = !finished;
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use crate::{
+    ecs::prelude::*,
+    math::{Unit, Vector3},
+};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum BillboardMode {
+    /// The billboard's +Z axis points at the camera's position, rotating freely about every axis.
+    Spherical,
+    /// The billboard's +Y axis is locked to the given world space axis, and it only rotates about
+    /// that axis to face the camera (trees, name plates, etc).
+    Cylindrical(Unit<Vector3<f32>>),
+    /// The billboard takes the rotation of the camera, so it is always parallel to the screen.
+    ScreenAligned,
+}
+
+impl Default for BillboardMode {
+    fn default() -> Self {
+        BillboardMode::Spherical
+    }
+}
+
+/// Rotates an entity to face a camera entity. The `BillboardSystem` runs after propagation and
+/// replaces the rotation in the entity's `LocalToWorld` (keeping its position and scale), then
+/// re-propagates so children of the billboard follow it.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Billboard {
+    pub camera: Entity,
+    pub mode: BillboardMode,
+}
+
+impl Billboard {
+    #[inline(always)]
+    pub fn new(camera: Entity, mode: BillboardMode) -> Self {
+        Self { camera, mode }
+    }
+
+    #[inline(always)]
+    pub fn spherical(camera: Entity) -> Self {
+        Self::new(camera, BillboardMode::Spherical)
+    }
+
+    #[inline(always)]
+    pub fn cylindrical(camera: Entity, axis: Unit<Vector3<f32>>) -> Self {
+        Self::new(camera, BillboardMode::Cylindrical(axis))
+    }
+
+    #[inline(always)]
+    pub fn screen_aligned(camera: Entity) -> Self {
+        Self::new(camera, BillboardMode::ScreenAligned)
+    }
+}
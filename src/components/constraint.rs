@@ -0,0 +1,74 @@
+use crate::{
+    ecs::prelude::*,
+    math::{Matrix4, UnitQuaternion, Vector3},
+};
+use shrinkwraprs::Shrinkwrap;
+use smallvec::SmallVec;
+
+/// One of the entities a constraint follows. Sources are blended by their normalized `weight`,
+/// and the `offset` is applied to the source before blending.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ConstraintSource<T> {
+    pub entity: Entity,
+    pub weight: f32,
+    pub offset: T,
+}
+
+impl<T> ConstraintSource<T> {
+    #[inline(always)]
+    pub fn new(entity: Entity, weight: f32, offset: T) -> Self {
+        Self {
+            entity,
+            weight,
+            offset,
+        }
+    }
+}
+
+/// Constrains the world position of an entity to the weighted average of its sources' world
+/// positions, each offset (in world space) by the source's `offset`.
+#[derive(Shrinkwrap, Debug, Default, PartialEq, Clone)]
+#[shrinkwrap(mutable)]
+pub struct PositionConstraint(pub SmallVec<[ConstraintSource<Vector3<f32>>; 4]>);
+
+/// Constrains the world rotation of an entity to the weighted average of its sources' world
+/// rotations, each followed by the source's `offset` rotation.
+#[derive(Shrinkwrap, Debug, Default, PartialEq, Clone)]
+#[shrinkwrap(mutable)]
+pub struct RotationConstraint(pub SmallVec<[ConstraintSource<UnitQuaternion<f32>>; 4]>);
+
+/// Constrains the world scale of an entity to the weighted average of its sources' world scales,
+/// each multiplied per-axis by the source's `offset`.
+#[derive(Shrinkwrap, Debug, Default, PartialEq, Clone)]
+#[shrinkwrap(mutable)]
+pub struct ScaleConstraint(pub SmallVec<[ConstraintSource<Vector3<f32>>; 4]>);
+
+/// Makes an entity behave as if it were parented to the weighted blend of its sources, without
+/// changing its `Parent`. The `offset` is the entity's rest transform relative to each source.
+#[derive(Shrinkwrap, Debug, Default, PartialEq, Clone)]
+#[shrinkwrap(mutable)]
+pub struct ParentConstraint(pub SmallVec<[ConstraintSource<Matrix4<f32>>; 4]>);
+
+impl PositionConstraint {
+    pub fn with(sources: &[ConstraintSource<Vector3<f32>>]) -> Self {
+        Self(SmallVec::from_slice(sources))
+    }
+}
+
+impl RotationConstraint {
+    pub fn with(sources: &[ConstraintSource<UnitQuaternion<f32>>]) -> Self {
+        Self(SmallVec::from_slice(sources))
+    }
+}
+
+impl ScaleConstraint {
+    pub fn with(sources: &[ConstraintSource<Vector3<f32>>]) -> Self {
+        Self(SmallVec::from_slice(sources))
+    }
+}
+
+impl ParentConstraint {
+    pub fn with(sources: &[ConstraintSource<Matrix4<f32>>]) -> Self {
+        Self(SmallVec::from_slice(sources))
+    }
+}
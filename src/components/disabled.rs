@@ -0,0 +1,19 @@
+use super::Inheritable;
+
+/// Disables the entity and its entire subtree, eg. for pooled or hidden objects. None of the
+/// transform systems update disabled entities, and their transforms are recomputed when they are
+/// enabled again.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct Disabled;
+
+impl Inheritable for Disabled {
+    fn inherit(&self, _parent: &Self) -> Self {
+        Disabled
+    }
+}
+
+/// Maintained by the `DisabledHierarchySystem` on every entity that is `Disabled` or has a
+/// `Disabled` ancestor (ie. has an `Inherited<Disabled>`), so that the transform systems can filter
+/// whole subtrees out of their queries. Don't add or remove it by hand.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct DisabledInHierarchy;
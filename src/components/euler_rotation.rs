@@ -0,0 +1,239 @@
+use crate::{
+    components::Rotation,
+    math::{Unit, UnitQuaternion, Vector3},
+};
+use std::{f32::consts::PI, fmt};
+
+/// The order in which the per-axis rotations of an `EulerRotation` are applied. Rotations are
+/// applied about the fixed axes of the parent space, so `Xyz` rotates about X first, then Y, then
+/// Z (ie. `Rz * Ry * Rx`, the same convention as `Rotation::from_euler_angles`).
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum RotationOrder {
+    #[default]
+    Xyz,
+    Xzy,
+    Yxz,
+    Yzx,
+    Zxy,
+    Zyx,
+}
+
+impl RotationOrder {
+    /// The axis indices in the order they are applied.
+    #[inline(always)]
+    pub fn axes(self) -> [usize; 3] {
+        match self {
+            RotationOrder::Xyz => [0, 1, 2],
+            RotationOrder::Xzy => [0, 2, 1],
+            RotationOrder::Yxz => [1, 0, 2],
+            RotationOrder::Yzx => [1, 2, 0],
+            RotationOrder::Zxy => [2, 0, 1],
+            RotationOrder::Zyx => [2, 1, 0],
+        }
+    }
+
+    /// `1.0` for the cyclic orders (`Xyz`, `Yzx`, `Zxy`), `-1.0` for the others.
+    #[inline(always)]
+    fn parity(self) -> f32 {
+        match self {
+            RotationOrder::Xyz | RotationOrder::Yzx | RotationOrder::Zxy => 1.0,
+            _ => -1.0,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum AngleUnit {
+    #[default]
+    Radians,
+    Degrees,
+}
+
+impl AngleUnit {
+    #[inline(always)]
+    pub fn to_radians(self, angle: f32) -> f32 {
+        match self {
+            AngleUnit::Radians => angle,
+            AngleUnit::Degrees => angle.to_radians(),
+        }
+    }
+
+    /// Converts an angle in radians into this unit.
+    #[inline(always)]
+    pub fn radians_to_unit(self, angle: f32) -> f32 {
+        match self {
+            AngleUnit::Radians => angle,
+            AngleUnit::Degrees => angle.to_degrees(),
+        }
+    }
+}
+
+/// A designer-friendly rotation, stored as one angle per axis along with the order and unit the
+/// angles are expressed in. The `EulerRotationSystem` writes it into the `Rotation` of the same
+/// entity whenever it changes.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct EulerRotation {
+    /// The rotation about the X, Y and Z axis (in that layout, regardless of `order`).
+    pub angles: Vector3<f32>,
+    pub order: RotationOrder,
+    pub unit: AngleUnit,
+}
+
+impl EulerRotation {
+    #[inline(always)]
+    pub fn new(x: f32, y: f32, z: f32, order: RotationOrder, unit: AngleUnit) -> Self {
+        Self {
+            angles: Vector3::new(x, y, z),
+            order,
+            unit,
+        }
+    }
+
+    #[inline(always)]
+    pub fn radians(x: f32, y: f32, z: f32, order: RotationOrder) -> Self {
+        Self::new(x, y, z, order, AngleUnit::Radians)
+    }
+
+    #[inline(always)]
+    pub fn degrees(x: f32, y: f32, z: f32, order: RotationOrder) -> Self {
+        Self::new(x, y, z, order, AngleUnit::Degrees)
+    }
+
+    #[inline(always)]
+    pub fn identity() -> Self {
+        Self::radians(0.0, 0.0, 0.0, RotationOrder::default())
+    }
+
+    /// The angles converted to radians.
+    pub fn to_radians(self) -> Vector3<f32> {
+        self.angles.map(|angle| self.unit.to_radians(angle))
+    }
+
+    pub fn to_quaternion(self) -> UnitQuaternion<f32> {
+        let radians = self.to_radians();
+        self.order
+            .axes()
+            .iter()
+            .fold(UnitQuaternion::identity(), |rotation, &axis| {
+                UnitQuaternion::from_axis_angle(&unit_axis(axis), radians[axis]) * rotation
+            })
+    }
+
+    /// Decomposes a rotation into Euler angles of the given order and unit. The middle angle is
+    /// kept within `[-90°, 90°]`.
+    pub fn from_quaternion(
+        rotation: &UnitQuaternion<f32>,
+        order: RotationOrder,
+        unit: AngleUnit,
+    ) -> Self {
+        let (radians, _) = decompose(rotation, order, 0.0);
+        Self::from_radians(radians, order, unit)
+    }
+
+    /// Decomposes a rotation into Euler angles in the order and unit of `hint`, picking whichever
+    /// of the equivalent solutions is closest to `hint`. Tools that display a `Rotation` every frame
+    /// should pass the previously displayed angles so values don't flip by 180° or wrap around.
+    pub fn from_quaternion_with_hint(rotation: &UnitQuaternion<f32>, hint: &EulerRotation) -> Self {
+        let hint_radians = hint.to_radians();
+        let [first, second, third] = hint.order.axes();
+        let (primary, alternative) = decompose(rotation, hint.order, hint_radians[first]);
+
+        let unwrap = |radians: Vector3<f32>| {
+            radians.zip_map(&hint_radians, |angle, hint| {
+                angle + ((hint - angle) / (2.0 * PI)).round() * 2.0 * PI
+            })
+        };
+        let primary = unwrap(primary);
+        let alternative = unwrap(alternative);
+        let distance = |radians: &Vector3<f32>| {
+            [first, second, third]
+                .iter()
+                .map(|&axis| (radians[axis] - hint_radians[axis]).abs())
+                .sum::<f32>()
+        };
+
+        let radians = if distance(&alternative) < distance(&primary) {
+            alternative
+        } else {
+            primary
+        };
+        Self::from_radians(radians, hint.order, hint.unit)
+    }
+
+    fn from_radians(radians: Vector3<f32>, order: RotationOrder, unit: AngleUnit) -> Self {
+        Self {
+            angles: radians.map(|angle| unit.radians_to_unit(angle)),
+            order,
+            unit,
+        }
+    }
+}
+
+impl Default for EulerRotation {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl From<EulerRotation> for Rotation {
+    fn from(euler: EulerRotation) -> Self {
+        Rotation(euler.to_quaternion())
+    }
+}
+
+impl fmt::Display for EulerRotation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "EulerRotation({}, {}, {}, {:?}, {:?})",
+            self.angles.x, self.angles.y, self.angles.z, self.order, self.unit
+        )
+    }
+}
+
+/// Returns both Euler solutions (in radians, indexed by axis) of `rotation` for `order`. When the
+/// rotation is in gimbal lock the first axis is pinned to `locked_first` and both solutions are the
+/// same.
+fn decompose(
+    rotation: &UnitQuaternion<f32>,
+    order: RotationOrder,
+    locked_first: f32,
+) -> (Vector3<f32>, Vector3<f32>) {
+    let [i, j, k] = order.axes();
+    let s = order.parity();
+    let m = rotation.to_rotation_matrix().into_inner();
+
+    let mut radians = Vector3::zeros();
+    let cos_second = m[(k, j)].hypot(m[(k, k)]);
+    radians[j] = (-s * m[(k, i)]).atan2(cos_second);
+
+    if cos_second > 1.0e-5 {
+        radians[i] = (s * m[(k, j)]).atan2(m[(k, k)]);
+        radians[k] = (s * m[(j, i)]).atan2(m[(i, i)]);
+
+        let mut alternative = radians;
+        alternative[i] = radians[i] + PI;
+        alternative[j] = PI - radians[j];
+        alternative[k] = radians[k] + PI;
+        (radians, alternative)
+    } else {
+        // Gimbal lock: only the combination of the first and last angle is known. Pin the first
+        // and solve for the last from `Rk * Rj = M * Ri^-1`.
+        radians[i] = locked_first;
+        let first = UnitQuaternion::from_axis_angle(&unit_axis(i), locked_first);
+        let n = (rotation * first.inverse())
+            .to_rotation_matrix()
+            .into_inner();
+        radians[k] = (-s * n[(i, j)]).atan2(n[(j, j)]);
+        (radians, radians)
+    }
+}
+
+#[inline(always)]
+fn unit_axis(axis: usize) -> Unit<Vector3<f32>> {
+    match axis {
+        0 => Vector3::x_axis(),
+        1 => Vector3::y_axis(),
+        _ => Vector3::z_axis(),
+    }
+}
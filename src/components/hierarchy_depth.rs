@@ -0,0 +1,8 @@
+use shrinkwraprs::Shrinkwrap;
+
+/// The depth of a child in its hierarchy, 1 for the children of a root. While the
+/// `PropagationMode` is `ByDepth`, it is maintained as a tag by the `HierarchyMaintenanceSystem`,
+/// so that each depth is stored in its own chunks and can be propagated in parallel.
+#[derive(Shrinkwrap, Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[shrinkwrap(mutable)]
+pub struct HierarchyDepth(pub u32);
@@ -0,0 +1,46 @@
+use crate::components::TransformTarget;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum IkSolver {
+    /// Analytic solver for a chain of exactly two bones (the end effector, its parent and its
+    /// grandparent). The chain bends towards `pole` if given, otherwise it keeps its current bend.
+    TwoBone { pole: Option<TransformTarget> },
+    /// Iterative FABRIK solver rotating the `joints` nearest ancestors of the end effector. Chains
+    /// with no joints are skipped.
+    Fabrik {
+        joints: usize,
+        iterations: usize,
+        tolerance: f32,
+    },
+}
+
+/// Inverse kinematics goal, added to the end effector of a chain of entities linked by `Parent`.
+/// The `IkSystem` runs after propagation, writes the local `Rotation` of each joint in the chain
+/// (honoring any `JointLimit`) and re-propagates the chain.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct IkChain {
+    pub target: TransformTarget,
+    pub solver: IkSolver,
+}
+
+impl IkChain {
+    #[inline(always)]
+    pub fn two_bone<T: Into<TransformTarget>>(target: T, pole: Option<TransformTarget>) -> Self {
+        Self {
+            target: target.into(),
+            solver: IkSolver::TwoBone { pole },
+        }
+    }
+
+    #[inline(always)]
+    pub fn fabrik<T: Into<TransformTarget>>(target: T, joints: usize) -> Self {
+        Self {
+            target: target.into(),
+            solver: IkSolver::Fabrik {
+                joints,
+                iterations: 10,
+                tolerance: 1.0e-3,
+            },
+        }
+    }
+}
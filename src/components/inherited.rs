@@ -0,0 +1,16 @@
+use crate::ecs::prelude::*;
+use shrinkwraprs::Shrinkwrap;
+
+/// A component whose value flows down the hierarchy, like a tint color or a time scale. Add an
+/// `inheritance_system` for the type to compute an `Inherited` value for every entity that has the
+/// component or descends from one that does.
+pub trait Inheritable: Component + Clone + PartialEq {
+    /// Combines the entity's own value with the value inherited by its parent.
+    fn inherit(&self, parent: &Self) -> Self;
+}
+
+/// The value of `T` an entity ends up with once its ancestors are taken into account. Entities
+/// without a `T` of their own inherit the value of their parent unchanged.
+#[derive(Shrinkwrap, Debug, PartialEq, Clone, Copy, Default)]
+#[shrinkwrap(mutable)]
+pub struct Inherited<T>(pub T);
@@ -0,0 +1,107 @@
+use crate::math::{Isometry3, Matrix4, Translation3, Unit, UnitQuaternion, Vector3};
+use shrinkwraprs::Shrinkwrap;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum JointKind {
+    /// Rotates about `axis` by the joint value (in radians), clamped to `[lower, upper]`.
+    Revolute {
+        axis: Unit<Vector3<f32>>,
+        lower: f32,
+        upper: f32,
+    },
+    /// Rotates about `axis` by the joint value (in radians), without limits.
+    Continuous { axis: Unit<Vector3<f32>> },
+    /// Translates along `axis` by the joint value, clamped to `[lower, upper]`.
+    Prismatic {
+        axis: Unit<Vector3<f32>>,
+        lower: f32,
+        upper: f32,
+    },
+    /// Does not move, the joint value is ignored.
+    Fixed,
+}
+
+/// An articulated joint between an entity and its `Parent`, in the style of URDF. The entity's
+/// `LocalToParent` is the static `origin` offset composed with the joint's motion for the current
+/// `JointValue`. Entities with a `Joint` get their `LocalToParent` from the joint rather than from
+/// `Translation`, `Rotation`, `Scale` or `NonUniformScale`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Joint {
+    pub origin: Isometry3<f32>,
+    pub kind: JointKind,
+}
+
+impl Joint {
+    #[inline(always)]
+    pub fn new(origin: Isometry3<f32>, kind: JointKind) -> Self {
+        Self { origin, kind }
+    }
+
+    #[inline(always)]
+    pub fn revolute(
+        origin: Isometry3<f32>,
+        axis: Unit<Vector3<f32>>,
+        lower: f32,
+        upper: f32,
+    ) -> Self {
+        Self::new(origin, JointKind::Revolute { axis, lower, upper })
+    }
+
+    #[inline(always)]
+    pub fn continuous(origin: Isometry3<f32>, axis: Unit<Vector3<f32>>) -> Self {
+        Self::new(origin, JointKind::Continuous { axis })
+    }
+
+    #[inline(always)]
+    pub fn prismatic(
+        origin: Isometry3<f32>,
+        axis: Unit<Vector3<f32>>,
+        lower: f32,
+        upper: f32,
+    ) -> Self {
+        Self::new(origin, JointKind::Prismatic { axis, lower, upper })
+    }
+
+    #[inline(always)]
+    pub fn fixed(origin: Isometry3<f32>) -> Self {
+        Self::new(origin, JointKind::Fixed)
+    }
+
+    /// The joint's value clamped to its limits, if it has any.
+    pub fn clamp(&self, value: f32) -> f32 {
+        match self.kind {
+            JointKind::Revolute { lower, upper, .. }
+            | JointKind::Prismatic { lower, upper, .. } => value.max(lower).min(upper),
+            JointKind::Continuous { .. } => value,
+            JointKind::Fixed => 0.0,
+        }
+    }
+
+    /// The motion of the joint for `value`, relative to its `origin`.
+    pub fn motion(&self, value: f32) -> Isometry3<f32> {
+        let value = self.clamp(value);
+        match self.kind {
+            JointKind::Revolute { axis, .. } | JointKind::Continuous { axis } => {
+                Isometry3::from_parts(
+                    Translation3::identity(),
+                    UnitQuaternion::from_axis_angle(&axis, value),
+                )
+            }
+            JointKind::Prismatic { axis, .. } => Isometry3::from_parts(
+                Translation3::from(axis.into_inner() * value),
+                UnitQuaternion::identity(),
+            ),
+            JointKind::Fixed => Isometry3::identity(),
+        }
+    }
+
+    /// The `LocalToParent` matrix of the joint for `value`.
+    pub fn local_to_parent(&self, value: f32) -> Matrix4<f32> {
+        (self.origin * self.motion(value)).to_homogeneous()
+    }
+}
+
+/// The scalar position of a `Joint` (an angle in radians or a distance), written by control code.
+#[derive(Shrinkwrap, Debug, PartialEq, Clone, Copy, Default)]
+#[shrinkwrap(mutable)]
+pub struct JointValue(pub f32);
@@ -0,0 +1,134 @@
+use crate::{
+    components::{AngleUnit, EulerRotation, RotationOrder},
+    math::{Quaternion, Unit, UnitQuaternion, Vector3},
+};
+use std::f32::consts::PI;
+
+/// Limits the local `Rotation` of an entity relative to its parent. The `JointLimitSystem` clamps
+/// the `Rotation` before `LocalToParent` is computed from it. All angles are in radians.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum JointLimit {
+    /// Limits the rotation to a cone of half-angle `swing` around `twist_axis`, and the rotation
+    /// about `twist_axis` itself to `[twist_min, twist_max]`.
+    SwingTwist {
+        twist_axis: Unit<Vector3<f32>>,
+        swing: f32,
+        twist_min: f32,
+        twist_max: f32,
+    },
+    /// Limits each Euler angle (decomposed in `order`) to `[min, max]`, per axis.
+    Euler {
+        order: RotationOrder,
+        min: Vector3<f32>,
+        max: Vector3<f32>,
+    },
+}
+
+impl JointLimit {
+    #[inline(always)]
+    pub fn swing_twist(
+        twist_axis: Unit<Vector3<f32>>,
+        swing: f32,
+        twist_min: f32,
+        twist_max: f32,
+    ) -> Self {
+        JointLimit::SwingTwist {
+            twist_axis,
+            swing,
+            twist_min,
+            twist_max,
+        }
+    }
+
+    #[inline(always)]
+    pub fn euler(order: RotationOrder, min: Vector3<f32>, max: Vector3<f32>) -> Self {
+        JointLimit::Euler { order, min, max }
+    }
+
+    /// Returns the closest rotation (per the limit's parameterization) that satisfies the limit.
+    pub fn clamp(&self, rotation: &UnitQuaternion<f32>) -> UnitQuaternion<f32> {
+        self.try_clamp(rotation).unwrap_or(*rotation)
+    }
+
+    /// Like `clamp`, but returns `None` if `rotation` already satisfies the limit.
+    pub fn try_clamp(&self, rotation: &UnitQuaternion<f32>) -> Option<UnitQuaternion<f32>> {
+        match self {
+            JointLimit::SwingTwist {
+                twist_axis,
+                swing,
+                twist_min,
+                twist_max,
+            } => {
+                let (swing_rotation, twist_rotation) = swing_twist(rotation, twist_axis);
+
+                let twist = twist_angle(&twist_rotation, twist_axis);
+                let twist_clamped = twist < *twist_min || twist > *twist_max;
+                let twist_rotation = if twist_clamped {
+                    UnitQuaternion::from_axis_angle(
+                        twist_axis,
+                        twist.max(*twist_min).min(*twist_max),
+                    )
+                } else {
+                    twist_rotation
+                };
+
+                let (swing_rotation, swing_clamped) = match swing_rotation.axis_angle() {
+                    Some((axis, angle)) if angle > *swing => {
+                        (UnitQuaternion::from_axis_angle(&axis, *swing), true)
+                    }
+                    _ => (swing_rotation, false),
+                };
+
+                if twist_clamped || swing_clamped {
+                    Some(swing_rotation * twist_rotation)
+                } else {
+                    None
+                }
+            }
+            JointLimit::Euler { order, min, max } => {
+                let mut euler =
+                    EulerRotation::from_quaternion(rotation, *order, AngleUnit::Radians);
+                let clamped = euler
+                    .angles
+                    .zip_zip_map(min, max, |angle, min, max| angle.max(min).min(max));
+                if clamped == euler.angles {
+                    None
+                } else {
+                    euler.angles = clamped;
+                    Some(euler.to_quaternion())
+                }
+            }
+        }
+    }
+}
+
+/// Decomposes `rotation` into `(swing, twist)` such that `rotation = swing * twist`, where `twist`
+/// is a rotation about `twist_axis` and `swing` is a rotation about an axis perpendicular to it.
+pub fn swing_twist(
+    rotation: &UnitQuaternion<f32>,
+    twist_axis: &Unit<Vector3<f32>>,
+) -> (UnitQuaternion<f32>, UnitQuaternion<f32>) {
+    let projected = twist_axis.as_ref() * rotation.imag().dot(twist_axis);
+    let twist = Quaternion::from_parts(rotation.scalar(), projected);
+
+    // A swing of exactly 180° leaves no twist to speak of.
+    let twist = if twist.norm_squared() < f32::EPSILON {
+        UnitQuaternion::identity()
+    } else {
+        UnitQuaternion::from_quaternion(twist)
+    };
+
+    (rotation * twist.inverse(), twist)
+}
+
+/// The signed angle, in `[-π, π]`, of a rotation about `axis`.
+pub fn twist_angle(twist: &UnitQuaternion<f32>, axis: &Unit<Vector3<f32>>) -> f32 {
+    let angle = 2.0 * twist.imag().dot(axis).atan2(twist.scalar());
+    if angle > PI {
+        angle - 2.0 * PI
+    } else if angle < -PI {
+        angle + 2.0 * PI
+    } else {
+        angle
+    }
+}
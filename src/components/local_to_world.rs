@@ -1,4 +1,5 @@
-use crate::math::Matrix4;
+use super::scale_compensation::rotation_of_basis;
+use crate::math::{Matrix4, Point3, UnitQuaternion, Vector3, U3};
 use shrinkwraprs::Shrinkwrap;
 use std::fmt;
 
@@ -11,6 +12,26 @@ impl LocalToWorld {
     pub fn identity() -> Self {
         Self(Matrix4::identity())
     }
+
+    /// The world space position of the entity's origin.
+    #[inline(always)]
+    pub fn position(&self) -> Point3<f32> {
+        Point3::from(self.0.column(3).xyz())
+    }
+
+    /// The world space rotation of the entity, ignoring any scale or shear.
+    pub fn rotation(&self) -> UnitQuaternion<f32> {
+        rotation_of_basis(&self.0.fixed_slice::<U3, U3>(0, 0).into_owned())
+    }
+
+    /// The length of each of the entity's axes in world space.
+    pub fn scale(&self) -> Vector3<f32> {
+        Vector3::new(
+            self.0.column(0).xyz().norm(),
+            self.0.column(1).xyz().norm(),
+            self.0.column(2).xyz().norm(),
+        )
+    }
 }
 
 impl Default for LocalToWorld {
@@ -0,0 +1,37 @@
+use crate::{components::TransformTarget, math::Vector3};
+
+/// Orients an entity so that its local +Z axis faces a target. The `LookAtSystem` runs after
+/// propagation, writes the entity's `Rotation` (relative to its parent, if it has one) and
+/// re-propagates the entity's subtree.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct LookAt {
+    pub target: TransformTarget,
+    /// The world space direction the entity's local +Y axis is kept closest to.
+    pub up: Vector3<f32>,
+    /// The maximum angular speed (in radians per second) the entity turns at, or `None` to snap to
+    /// the target immediately. The frame time is read from the `AnimationTime` resource.
+    pub max_angular_speed: Option<f32>,
+}
+
+impl LookAt {
+    #[inline(always)]
+    pub fn new<T: Into<TransformTarget>>(target: T) -> Self {
+        Self {
+            target: target.into(),
+            up: Vector3::y(),
+            max_angular_speed: None,
+        }
+    }
+
+    #[inline(always)]
+    pub fn with_up(mut self, up: Vector3<f32>) -> Self {
+        self.up = up;
+        self
+    }
+
+    #[inline(always)]
+    pub fn with_max_angular_speed(mut self, max_angular_speed: f32) -> Self {
+        self.max_angular_speed = Some(max_angular_speed);
+        self
+    }
+}
@@ -1,17 +1,63 @@
+mod animation_layers;
+mod animation_player;
+mod billboard;
 mod children;
+mod constraint;
+mod disabled;
+mod euler_rotation;
+mod hierarchy_depth;
+mod ik_chain;
+mod inherited;
+mod joint;
+mod joint_limit;
 mod local_to_parent;
 mod local_to_world;
+mod look_at;
+mod name;
+mod name_index;
 mod non_uniform_scale;
 mod parent;
+mod rest_pose;
+mod retarget;
+mod root_motion;
 mod rotation;
 mod scale;
+mod scale_compensation;
+mod skeleton;
+mod skin;
+mod socket;
+mod static_transform;
+mod target;
 mod translation;
 
+pub use animation_layers::*;
+pub use animation_player::*;
+pub use billboard::*;
 pub use children::Children;
+pub use constraint::*;
+pub use disabled::*;
+pub use euler_rotation::*;
+pub use hierarchy_depth::*;
+pub use ik_chain::*;
+pub use inherited::*;
+pub use joint::*;
+pub use joint_limit::*;
 pub use local_to_parent::*;
 pub use local_to_world::*;
+pub use look_at::*;
+pub use name::*;
+pub use name_index::*;
 pub use non_uniform_scale::*;
 pub use parent::{Parent, PreviousParent};
+pub use rest_pose::*;
+pub use retarget::*;
+pub use root_motion::*;
 pub use rotation::*;
 pub use scale::*;
+pub use scale_compensation::*;
+pub use skeleton::*;
+pub use skin::*;
+pub use socket::*;
+pub use static_transform::*;
+pub use target::*;
 pub use translation::*;
@@ -0,0 +1,35 @@
+use shrinkwraprs::Shrinkwrap;
+use std::fmt;
+
+/// The name of an entity, used to address it by path (eg. `"spine/arm_l/hand_l"`) relative to an
+/// ancestor.
+#[derive(Shrinkwrap, Debug, PartialEq, Eq, Hash, Clone, Default)]
+#[shrinkwrap(mutable)]
+pub struct Name(pub String);
+
+impl Name {
+    #[inline(always)]
+    pub fn new<S: Into<String>>(name: S) -> Self {
+        Self(name.into())
+    }
+}
+
+impl From<&str> for Name {
+    #[inline(always)]
+    fn from(name: &str) -> Self {
+        Self(name.to_owned())
+    }
+}
+
+impl From<String> for Name {
+    #[inline(always)]
+    fn from(name: String) -> Self {
+        Self(name)
+    }
+}
+
+impl fmt::Display for Name {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
@@ -0,0 +1,46 @@
+use crate::ecs::prelude::*;
+use std::collections::HashMap;
+
+/// An index of every named descendant of the entity, by path (see `hierarchy_path`). Add it to the
+/// roots that are looked up often; the `NameIndexSystem` rebuilds it whenever `Children` or `Name`s
+/// change.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct NameIndex {
+    pub paths: HashMap<String, Entity>,
+    built: bool,
+}
+
+impl NameIndex {
+    #[inline(always)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replaces the indexed paths, marking the index as built.
+    #[inline(always)]
+    pub fn set_paths(&mut self, paths: HashMap<String, Entity>) {
+        self.paths = paths;
+        self.built = true;
+    }
+
+    /// Whether the index has been built at least once.
+    #[inline(always)]
+    pub fn is_built(&self) -> bool {
+        self.built
+    }
+
+    #[inline(always)]
+    pub fn get(&self, path: &str) -> Option<Entity> {
+        self.paths.get(path.trim_matches('/')).copied()
+    }
+
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.paths.len()
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+}
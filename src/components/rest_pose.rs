@@ -0,0 +1,30 @@
+use crate::math::{UnitQuaternion, Vector3};
+
+/// The local translation and rotation of a bone in its skeleton's bind (rest) pose, used to
+/// retarget animation between skeletons whose bones are oriented or sized differently.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct RestPose {
+    pub translation: Vector3<f32>,
+    pub rotation: UnitQuaternion<f32>,
+}
+
+impl RestPose {
+    #[inline(always)]
+    pub fn new(translation: Vector3<f32>, rotation: UnitQuaternion<f32>) -> Self {
+        Self {
+            translation,
+            rotation,
+        }
+    }
+
+    #[inline(always)]
+    pub fn identity() -> Self {
+        Self::new(Vector3::zeros(), UnitQuaternion::identity())
+    }
+}
+
+impl Default for RestPose {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
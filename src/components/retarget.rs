@@ -0,0 +1,32 @@
+use crate::ecs::prelude::*;
+
+/// Drives the skeleton under the entity it is added to from the skeleton under `source`. Bones are
+/// matched by `Name`, and both skeletons need a `RestPose` on their bones. The `RetargetSystem`
+/// writes the `Rotation` of every matched bone and the `Translation` of the `hips` bone.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Retarget {
+    /// The root of the animated source hierarchy.
+    pub source: Entity,
+    /// Name of the bone whose translation is retargeted, usually the pelvis.
+    pub hips: String,
+    /// Factor applied to the translation of the hips. When `None`, the ratio between the distance
+    /// from the root to the hips in both rest poses (ie. the leg length) is used.
+    pub translation_scale: Option<f32>,
+}
+
+impl Retarget {
+    #[inline(always)]
+    pub fn new<S: Into<String>>(source: Entity, hips: S) -> Self {
+        Self {
+            source,
+            hips: hips.into(),
+            translation_scale: None,
+        }
+    }
+
+    #[inline(always)]
+    pub fn with_translation_scale(mut self, translation_scale: f32) -> Self {
+        self.translation_scale = Some(translation_scale);
+        self
+    }
+}
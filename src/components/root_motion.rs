@@ -0,0 +1,66 @@
+use crate::math::{Unit, UnitQuaternion, Vector3};
+
+/// The local transform of the root motion bone as last seen by the `RootMotionSystem`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct RootMotionSample {
+    pub translation: Vector3<f32>,
+    pub rotation: UnitQuaternion<f32>,
+    /// Playback position of the entity's `AnimationPlayer`, if it has one.
+    pub time: Option<f32>,
+}
+
+/// Moves the entity it is added to by the motion the animation applies to one of its descendants
+/// (usually the hips). Each time the `RootMotionSystem` runs, the change in the bone's `Translation`
+/// (along `translation_axes`) and heading (its rotation about `up`) is removed from the bone and
+/// accumulated onto this entity's `Translation` and `Rotation`. The bone is expected to be animated
+/// in the space of this entity, ie. to be a direct child of it.
+#[derive(Debug, PartialEq, Clone)]
+pub struct RootMotion {
+    /// Path of the bone relative to this entity, see `Channel::path`.
+    pub bone: String,
+    /// Per-axis factor of the bone's translation to extract, `(1, 0, 1)` by default so that the
+    /// bone keeps its vertical motion.
+    pub translation_axes: Vector3<f32>,
+    pub up: Unit<Vector3<f32>>,
+    pub extract_rotation: bool,
+    /// The bone's transform when root motion started, which it is held at.
+    pub reference: Option<RootMotionSample>,
+    /// The bone's animated transform the last time the system ran.
+    pub previous: Option<RootMotionSample>,
+    /// The bone's transform as written by the system, used to skip runs where the bone was not
+    /// animated.
+    pub written: Option<RootMotionSample>,
+}
+
+impl RootMotion {
+    #[inline(always)]
+    pub fn new<S: Into<String>>(bone: S) -> Self {
+        Self {
+            bone: bone.into(),
+            translation_axes: Vector3::new(1.0, 0.0, 1.0),
+            up: Vector3::y_axis(),
+            extract_rotation: true,
+            reference: None,
+            previous: None,
+            written: None,
+        }
+    }
+
+    #[inline(always)]
+    pub fn with_translation_axes(mut self, translation_axes: Vector3<f32>) -> Self {
+        self.translation_axes = translation_axes;
+        self
+    }
+
+    #[inline(always)]
+    pub fn with_up(mut self, up: Unit<Vector3<f32>>) -> Self {
+        self.up = up;
+        self
+    }
+
+    #[inline(always)]
+    pub fn without_rotation(mut self) -> Self {
+        self.extract_rotation = false;
+        self
+    }
+}
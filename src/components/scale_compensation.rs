@@ -0,0 +1,81 @@
+use crate::math::{Matrix3, Matrix4, Point3, Rotation3, UnitQuaternion, U3};
+
+/// Opt-in control over how a child in a hierarchy inherits the scale of its parent. Children
+/// without this component simply compute `Parent.LocalToWorld * LocalToParent`, which skews the
+/// child when it is rotated under a `NonUniformScale` parent.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ScaleCompensation {
+    /// Maya-style "segment scale compensate". The child follows the position (including the
+    /// scaled offset) and rotation of its parent, but none of the parent's scale is applied to the
+    /// child's own axes.
+    SegmentScale,
+    /// The inherited scale is kept, but only along the child's own axes. Each axis keeps the length
+    /// it would have had without compensation while the shear between axes is removed.
+    AlongAxes,
+}
+
+impl ScaleCompensation {
+    /// Composes a parent's `LocalToWorld` with a child's `LocalToParent` matrix, removing any shear
+    /// the parent would have introduced.
+    pub fn compose(self, parent: &Matrix4<f32>, local: &Matrix4<f32>) -> Matrix4<f32> {
+        let parent_rotation = rotation_of(parent);
+        let local_basis = local.fixed_slice::<U3, U3>(0, 0).into_owned();
+        let position = parent.transform_point(&Point3::from(local.column(3).xyz()));
+
+        let basis = match self {
+            ScaleCompensation::SegmentScale => parent_rotation.to_rotation_matrix() * local_basis,
+            ScaleCompensation::AlongAxes => {
+                let sheared = parent.fixed_slice::<U3, U3>(0, 0) * local_basis;
+                let rotation = (parent_rotation * rotation_of_basis(&local_basis))
+                    .to_rotation_matrix()
+                    .into_inner();
+                let mut basis = Matrix3::zeros();
+                for i in 0..3 {
+                    let axis = rotation.column(i);
+                    let column = sheared.column(i);
+                    let length = column.norm();
+                    basis.set_column(i, &(axis * length.copysign(axis.dot(&column))));
+                }
+                basis
+            }
+        };
+
+        let mut result = basis.to_homogeneous();
+        result.set_column(3, &position.to_homogeneous());
+        result
+    }
+}
+
+/// The rotation closest to the upper 3x3 of an affine matrix, ignoring any scale or shear.
+fn rotation_of(matrix: &Matrix4<f32>) -> UnitQuaternion<f32> {
+    rotation_of_basis(&matrix.fixed_slice::<U3, U3>(0, 0).into_owned())
+}
+
+/// Iterations allowed to refine the rotation of a sheared basis. Without shear the orthonormalized
+/// guess is already exact and the first iteration stops.
+const MAX_ROTATION_ITERATIONS: usize = 128;
+
+/// The rotation closest to a scaled and/or sheared basis. `UnitQuaternion::from_matrix` iterates
+/// until convergence with no upper bound, which degenerate or NaN input may never reach, so this
+/// starts from the orthonormalized basis and caps the refinement instead.
+pub(crate) fn rotation_of_basis(basis: &Matrix3<f32>) -> UnitQuaternion<f32> {
+    let guess = orthonormalized(basis).unwrap_or_else(Rotation3::identity);
+    UnitQuaternion::from_rotation_matrix(&Rotation3::from_matrix_eps(
+        basis,
+        f32::EPSILON,
+        MAX_ROTATION_ITERATIONS,
+        guess,
+    ))
+}
+
+/// Gram-Schmidt on the X and Y axes of `basis`, or `None` if they are (nearly) parallel or zero.
+fn orthonormalized(basis: &Matrix3<f32>) -> Option<Rotation3<f32>> {
+    let x = basis.column(0).try_normalize(f32::EPSILON)?;
+    let y = basis.column(1).into_owned();
+    let y = (y - x * x.dot(&y)).try_normalize(f32::EPSILON)?;
+    Some(Rotation3::from_matrix_unchecked(Matrix3::from_columns(&[
+        x,
+        y,
+        x.cross(&y),
+    ])))
+}
@@ -0,0 +1,107 @@
+use crate::{
+    ecs::prelude::*,
+    math::{Matrix4, UnitQuaternion, Vector3},
+};
+use shrinkwraprs::Shrinkwrap;
+
+/// A whole bone hierarchy stored on a single entity, as an alternative to one entity per bone.
+/// Bones are stored as parallel arrays, and a bone's parent always comes before it, so the
+/// `SkeletonSystem` computes the pose of every bone in a single pass, relative to the entity's
+/// `LocalToWorld`.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Skeleton {
+    pub names: Vec<String>,
+    /// Index of each bone's parent, `None` for bones attached to the entity itself.
+    pub parents: Vec<Option<usize>>,
+    pub translations: Vec<Vector3<f32>>,
+    pub rotations: Vec<UnitQuaternion<f32>>,
+    pub scales: Vec<Vector3<f32>>,
+}
+
+impl Skeleton {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            names: Vec::with_capacity(capacity),
+            parents: Vec::with_capacity(capacity),
+            translations: Vec::with_capacity(capacity),
+            rotations: Vec::with_capacity(capacity),
+            scales: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Appends a bone and returns its index. The parent must already have been added.
+    pub fn add_bone<S: Into<String>>(
+        &mut self,
+        name: S,
+        parent: Option<usize>,
+        translation: Vector3<f32>,
+        rotation: UnitQuaternion<f32>,
+    ) -> usize {
+        assert!(
+            parent.map_or(true, |parent| parent < self.len()),
+            "The parent of a bone must be added before it"
+        );
+        self.names.push(name.into());
+        self.parents.push(parent);
+        self.translations.push(translation);
+        self.rotations.push(rotation);
+        self.scales.push(Vector3::repeat(1.0));
+        self.len() - 1
+    }
+
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.parents.len()
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.parents.is_empty()
+    }
+
+    pub fn bone_index(&self, name: &str) -> Option<usize> {
+        self.names.iter().position(|bone| bone == name)
+    }
+
+    /// The transform of a bone relative to its parent, composed the same way as `LocalToParent`.
+    pub fn local_matrix(&self, bone: usize) -> Matrix4<f32> {
+        let mut matrix = self.rotations[bone].to_homogeneous();
+        matrix.append_translation_mut(&self.translations[bone]);
+        matrix.prepend_nonuniform_scaling_mut(&self.scales[bone]);
+        matrix
+    }
+
+    /// Computes the transform of every bone, given the transform of the skeleton's entity.
+    pub fn compute_pose(&self, local_to_world: &Matrix4<f32>, pose: &mut Vec<Matrix4<f32>>) {
+        pose.clear();
+        pose.reserve(self.len());
+        for bone in 0..self.len() {
+            let parent = match self.parents[bone] {
+                Some(parent) => pose[parent],
+                None => *local_to_world,
+            };
+            pose.push(parent * self.local_matrix(bone));
+        }
+    }
+}
+
+/// The world space transform of every bone of the entity's `Skeleton`, by bone index.
+#[derive(Shrinkwrap, Debug, PartialEq, Clone, Default)]
+#[shrinkwrap(mutable)]
+pub struct SkeletonPose(pub Vec<Matrix4<f32>>);
+
+/// Attaches an entity (which should not have a `Parent`) to a bone of another entity's `Skeleton`.
+/// The `SkeletonSystem` computes its `LocalToWorld` from the bone's transform and the entity's own
+/// `Translation`, `Rotation` and scale, then propagates it to the entity's `Children`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct BoneAttachment {
+    pub skeleton: Entity,
+    pub bone: usize,
+}
+
+impl BoneAttachment {
+    #[inline(always)]
+    pub fn new(skeleton: Entity, bone: usize) -> Self {
+        Self { skeleton, bone }
+    }
+}
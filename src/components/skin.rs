@@ -0,0 +1,69 @@
+use crate::{
+    dual_quaternion::DualQuaternion,
+    ecs::prelude::*,
+    math::{Matrix3, Matrix4},
+};
+use shrinkwraprs::Shrinkwrap;
+
+/// The palette components the `SkinningSystem` fills for a `Skin`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SkinningOutput {
+    /// A `JointPalette`, for linear blend skinning.
+    Matrices,
+    /// A `DualQuaternionPalette`, for dual quaternion skinning.
+    DualQuaternions,
+}
+
+impl Default for SkinningOutput {
+    fn default() -> Self {
+        SkinningOutput::Matrices
+    }
+}
+
+/// Binds a skinned mesh to the joint entities deforming it. The `SkinningSystem` fills the mesh's
+/// `JointPalette` (or `DualQuaternionPalette`) from the joints' `LocalToWorld`.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Skin {
+    pub joints: Vec<Entity>,
+    /// Transforms mesh space into the space of each joint in the bind pose, one per joint.
+    pub inverse_bind_matrices: Vec<Matrix4<f32>>,
+    pub output: SkinningOutput,
+}
+
+impl Skin {
+    /// Panics if there isn't exactly one inverse bind matrix per joint.
+    pub fn new(joints: Vec<Entity>, inverse_bind_matrices: Vec<Matrix4<f32>>) -> Self {
+        assert_eq!(
+            joints.len(),
+            inverse_bind_matrices.len(),
+            "A skin needs one inverse bind matrix per joint"
+        );
+        Self {
+            joints,
+            inverse_bind_matrices,
+            output: SkinningOutput::default(),
+        }
+    }
+
+    #[inline(always)]
+    pub fn with_output(mut self, output: SkinningOutput) -> Self {
+        self.output = output;
+        self
+    }
+}
+
+/// One skinning matrix per joint of the entity's `Skin`, in the same order, transforming bind pose
+/// mesh space vertices into the joint's current pose, relative to the mesh's `LocalToWorld`. The
+/// matrices are contiguous column-major `f32`s, ready to be uploaded.
+#[derive(Shrinkwrap, Debug, PartialEq, Clone, Default)]
+#[shrinkwrap(mutable)]
+pub struct JointPalette(pub Vec<Matrix4<f32>>);
+
+/// The skinning transforms of a `Skin` split into a rigid part, as dual quaternions, and a
+/// scale/shear part applied to vertices beforehand, ie. `dual_quaternions[i] * (scales[i] * v)`.
+/// The scale of joints without any scale or shear is the identity.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct DualQuaternionPalette {
+    pub dual_quaternions: Vec<DualQuaternion>,
+    pub scales: Vec<Matrix3<f32>>,
+}
@@ -0,0 +1,68 @@
+use crate::{
+    ecs::prelude::*,
+    math::{Matrix4, UnitQuaternion, Vector3},
+};
+use shrinkwraprs::Shrinkwrap;
+use smallvec::SmallVec;
+
+/// A named attachment point, offset from the entity that has it.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Socket {
+    pub name: String,
+    pub offset: Matrix4<f32>,
+}
+
+impl Socket {
+    #[inline(always)]
+    pub fn new<S: Into<String>>(name: S, offset: Matrix4<f32>) -> Self {
+        Self {
+            name: name.into(),
+            offset,
+        }
+    }
+
+    #[inline(always)]
+    pub fn from_translation_rotation<S: Into<String>>(
+        name: S,
+        translation: Vector3<f32>,
+        rotation: UnitQuaternion<f32>,
+    ) -> Self {
+        let mut offset = rotation.to_homogeneous();
+        offset.append_translation_mut(&translation);
+        Self::new(name, offset)
+    }
+}
+
+/// The sockets children can be attached to with a `SocketAttachment`.
+#[derive(Shrinkwrap, Debug, Default, PartialEq, Clone)]
+#[shrinkwrap(mutable)]
+pub struct Sockets(pub SmallVec<[Socket; 4]>);
+
+impl Sockets {
+    pub fn with(sockets: &[Socket]) -> Self {
+        Self(sockets.iter().cloned().collect())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Socket> {
+        self.0.iter().find(|socket| socket.name == name)
+    }
+}
+
+/// Attaches an entity to a named socket of `target`. The `SocketSystem` makes `target` the entity's
+/// `Parent`, and propagation places the entity at `Parent.LocalToWorld * Socket.offset *
+/// LocalToParent`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SocketAttachment {
+    pub target: Entity,
+    pub socket: String,
+}
+
+impl SocketAttachment {
+    #[inline(always)]
+    pub fn new<S: Into<String>>(target: Entity, socket: S) -> Self {
+        Self {
+            target,
+            socket: socket.into(),
+        }
+    }
+}
@@ -0,0 +1,12 @@
+/// Marks the entity and its entire subtree as static. The `StaticBakeSystem` computes their final
+/// `LocalToWorld` once, then removes the rest of their transform data so that no transform system
+/// touches them again.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct Static;
+
+/// Added by the `StaticBakeSystem` to the entities it baked. Their `LocalToWorld` is final, and
+/// they no longer have a `Translation`, `Rotation`, `Scale`, `NonUniformScale`, `EulerRotation`,
+/// `LocalToParent`, `Parent` or `Children`. Drivers such as `LookAt`, `IkChain`, `Billboard` or the
+/// constraints are ignored on them.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct StaticTransform;
@@ -0,0 +1,21 @@
+use crate::{ecs::prelude::*, math::Point3};
+
+/// Something an entity can be aimed at: either another entity (by its `LocalToWorld` position) or
+/// a fixed point in world space.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum TransformTarget {
+    Entity(Entity),
+    Point(Point3<f32>),
+}
+
+impl From<Entity> for TransformTarget {
+    fn from(entity: Entity) -> Self {
+        TransformTarget::Entity(entity)
+    }
+}
+
+impl From<Point3<f32>> for TransformTarget {
+    fn from(point: Point3<f32>) -> Self {
+        TransformTarget::Point(point)
+    }
+}
@@ -0,0 +1,489 @@
+#![allow(dead_code)]
+use crate::{
+    components::*,
+    ecs::{prelude::*, systems::SubWorld},
+    local_to_world_propagate_system,
+    local_transform::LocalTransformContribution,
+    local_transform_system,
+    math::{Matrix4, Point3, Quaternion, UnitQuaternion, Vector3, Vector4},
+};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+pub fn build(_: &mut World, resources: &mut Resources) -> Box<dyn Schedulable> {
+    let (contributions, _) = local_transform_system::contributions(resources);
+    let builder = SystemBuilder::<()>::new("ConstraintSystem");
+    let builder = local_transform_system::declare_compose_access(&contributions, builder);
+    local_to_world_propagate_system::declare_repropagate_access(builder)
+        .with_query(
+            <Read<PositionConstraint>>::query()
+                .filter(component::<LocalToWorld>() & !component::<StaticTransform>()),
+        )
+        .with_query(
+            <Read<RotationConstraint>>::query()
+                .filter(component::<LocalToWorld>() & !component::<StaticTransform>()),
+        )
+        .with_query(
+            <Read<ScaleConstraint>>::query()
+                .filter(component::<LocalToWorld>() & !component::<StaticTransform>()),
+        )
+        .with_query(
+            <Read<ParentConstraint>>::query()
+                .filter(component::<LocalToWorld>() & !component::<StaticTransform>()),
+        )
+        .build(move |_commands, world, _resource, queries| {
+            let (position_query, rotation_query, scale_query, parent_query) = queries;
+
+            // Collect every constrained entity, along with all of its constraints. The entities
+            // are kept in query order, so that the evaluation order doesn't depend on hashing.
+            let mut entities = Vec::new();
+            let mut constrained = HashMap::<Entity, Constraints>::new();
+            for (entity, constraint) in position_query.iter_entities(world) {
+                constraints_of(&mut constrained, &mut entities, entity).position =
+                    Some(constraint.clone());
+            }
+            for (entity, constraint) in rotation_query.iter_entities(world) {
+                constraints_of(&mut constrained, &mut entities, entity).rotation =
+                    Some(constraint.clone());
+            }
+            for (entity, constraint) in scale_query.iter_entities(world) {
+                constraints_of(&mut constrained, &mut entities, entity).scale =
+                    Some(constraint.clone());
+            }
+            for (entity, constraint) in parent_query.iter_entities(world) {
+                constraints_of(&mut constrained, &mut entities, entity).parent =
+                    Some(constraint.clone());
+            }
+
+            for entity in evaluation_order(world, &entities, &constrained) {
+                let local_to_world = evaluate(world, &contributions, entity, &constrained[&entity]);
+                local_to_world_propagate_system::repropagate(world, entity, local_to_world);
+            }
+        })
+}
+
+fn constraints_of<'a>(
+    constrained: &'a mut HashMap<Entity, Constraints>,
+    entities: &mut Vec<Entity>,
+    entity: Entity,
+) -> &'a mut Constraints {
+    constrained.entry(entity).or_insert_with(|| {
+        entities.push(entity);
+        Constraints::default()
+    })
+}
+
+#[derive(Default)]
+struct Constraints {
+    position: Option<PositionConstraint>,
+    rotation: Option<RotationConstraint>,
+    scale: Option<ScaleConstraint>,
+    parent: Option<ParentConstraint>,
+}
+
+impl Constraints {
+    fn sources(&self) -> impl Iterator<Item = Entity> + '_ {
+        let position = self
+            .position
+            .iter()
+            .flat_map(|c| c.iter().map(|s| s.entity));
+        let rotation = self
+            .rotation
+            .iter()
+            .flat_map(|c| c.iter().map(|s| s.entity));
+        let scale = self.scale.iter().flat_map(|c| c.iter().map(|s| s.entity));
+        let parent = self.parent.iter().flat_map(|c| c.iter().map(|s| s.entity));
+        position.chain(rotation).chain(scale).chain(parent)
+    }
+}
+
+/// Orders constrained entities so that an entity is only evaluated once every constrained entity
+/// it depends on has been. An entity depends on another constrained entity if one of its sources is
+/// that entity or a descendant of it, or if that entity is one of its ancestors (re-propagating the
+/// ancestor would otherwise overwrite it). Entities that don't depend on each other are evaluated
+/// in the order of `entities`.
+fn evaluation_order(
+    world: &SubWorld,
+    entities: &[Entity],
+    constrained: &HashMap<Entity, Constraints>,
+) -> Vec<Entity> {
+    let mut dependents = HashMap::<Entity, Vec<Entity>>::new();
+    let mut dependency_counts = HashMap::<Entity, usize>::new();
+    let parent_of = |entity: Entity| world.get_component::<Parent>(entity).map(|parent| parent.0);
+
+    for entity in entities {
+        let constraints = &constrained[entity];
+        let mut dependencies = HashSet::new();
+        // Walk from each source, and from the entity's parent, up to the root.
+        let starts = constraints
+            .sources()
+            .map(Some)
+            .chain(std::iter::once(parent_of(*entity)));
+        for start in starts {
+            let mut current = start;
+            while let Some(ancestor) = current {
+                if ancestor != *entity && constrained.contains_key(&ancestor) {
+                    dependencies.insert(ancestor);
+                }
+                current = parent_of(ancestor);
+            }
+        }
+
+        dependency_counts.insert(*entity, dependencies.len());
+        for dependency in dependencies {
+            dependents.entry(dependency).or_default().push(*entity);
+        }
+    }
+
+    let mut ready = entities
+        .iter()
+        .filter(|entity| dependency_counts[entity] == 0)
+        .copied()
+        .collect::<VecDeque<_>>();
+    let mut order = Vec::with_capacity(entities.len());
+
+    while let Some(entity) = ready.pop_front() {
+        order.push(entity);
+        for dependent in dependents.remove(&entity).unwrap_or_default() {
+            let count = dependency_counts.get_mut(&dependent).unwrap();
+            *count -= 1;
+            if *count == 0 {
+                ready.push_back(dependent);
+            }
+        }
+    }
+
+    if order.len() < entities.len() {
+        for entity in entities {
+            if dependency_counts[entity] > 0 {
+                log::warn!(
+                    "Entity {} is part of a constraint cycle, evaluating it in arbitrary order",
+                    entity
+                );
+                order.push(*entity);
+            }
+        }
+    }
+
+    order
+}
+
+fn evaluate(
+    world: &SubWorld,
+    contributions: &[LocalTransformContribution],
+    entity: Entity,
+    constraints: &Constraints,
+) -> LocalToWorld {
+    let mut local_to_world = world
+        .get_component::<LocalToWorld>(entity)
+        .map(|e| *e)
+        .unwrap_or_default();
+
+    if let Some(constraint) = &constraints.parent {
+        let sources = constraint
+            .iter()
+            .filter_map(|source| {
+                world
+                    .get_component::<LocalToWorld>(source.entity)
+                    .map(|e| (source.weight, LocalToWorld(e.0 * source.offset)))
+            })
+            .collect::<Vec<_>>();
+        if let Some((position, rotation, scale)) = blend(&sources) {
+            let parent = compose(&position, &rotation, &scale);
+            let local = local_transform_system::compose_entity(contributions, world, entity)
+                .unwrap_or_else(Matrix4::identity);
+            local_to_world = LocalToWorld(parent * local);
+        }
+    }
+
+    let mut position = local_to_world.position();
+    let mut rotation = local_to_world.rotation();
+    let mut scale = local_to_world.scale();
+
+    if let Some(constraint) = &constraints.position {
+        let sources = constraint.iter().filter_map(|source| {
+            world
+                .get_component::<LocalToWorld>(source.entity)
+                .map(|e| (source.weight, e.position() + source.offset))
+        });
+        if let Some(blended) = blend_points(sources) {
+            position = blended;
+        }
+    }
+
+    if let Some(constraint) = &constraints.rotation {
+        let sources = constraint.iter().filter_map(|source| {
+            world
+                .get_component::<LocalToWorld>(source.entity)
+                .map(|e| (source.weight, e.rotation() * source.offset))
+        });
+        if let Some(blended) = blend_rotations(sources) {
+            rotation = blended;
+        }
+    }
+
+    if let Some(constraint) = &constraints.scale {
+        let sources = constraint.iter().filter_map(|source| {
+            world
+                .get_component::<LocalToWorld>(source.entity)
+                .map(|e| (source.weight, e.scale().component_mul(&source.offset)))
+        });
+        if let Some(blended) = blend_vectors(sources) {
+            scale = blended;
+        }
+    }
+
+    LocalToWorld(compose(&position, &rotation, &scale))
+}
+
+fn compose(
+    position: &Point3<f32>,
+    rotation: &UnitQuaternion<f32>,
+    scale: &Vector3<f32>,
+) -> Matrix4<f32> {
+    rotation
+        .to_homogeneous()
+        .append_translation(&position.coords)
+        .prepend_nonuniform_scaling(scale)
+}
+
+/// Blends world transforms by decomposing them into position, rotation and scale.
+fn blend(
+    sources: &[(f32, LocalToWorld)],
+) -> Option<(Point3<f32>, UnitQuaternion<f32>, Vector3<f32>)> {
+    Some((
+        blend_points(sources.iter().map(|(w, e)| (*w, e.position())))?,
+        blend_rotations(sources.iter().map(|(w, e)| (*w, e.rotation())))?,
+        blend_vectors(sources.iter().map(|(w, e)| (*w, e.scale())))?,
+    ))
+}
+
+fn blend_points<I: Iterator<Item = (f32, Point3<f32>)>>(sources: I) -> Option<Point3<f32>> {
+    blend_vectors(sources.map(|(weight, point)| (weight, point.coords))).map(Point3::from)
+}
+
+fn blend_vectors<I: Iterator<Item = (f32, Vector3<f32>)>>(sources: I) -> Option<Vector3<f32>> {
+    let (sum, total_weight) = sources.fold(
+        (Vector3::zeros(), 0.0),
+        |(sum, total_weight), (weight, vector)| (sum + vector * weight, total_weight + weight),
+    );
+
+    if total_weight > f32::EPSILON {
+        Some(sum / total_weight)
+    } else {
+        None
+    }
+}
+
+/// A normalized, weighted sum of the rotations. Each rotation is flipped into the same hemisphere
+/// as the first so that `q` and `-q` don't cancel each other out.
+fn blend_rotations<I: Iterator<Item = (f32, UnitQuaternion<f32>)>>(
+    sources: I,
+) -> Option<UnitQuaternion<f32>> {
+    let mut first: Option<Vector4<f32>> = None;
+    let mut sum = Vector4::zeros();
+
+    for (weight, rotation) in sources {
+        let coords = rotation.into_inner().coords;
+        let reference = *first.get_or_insert(coords);
+        let sign = if reference.dot(&coords) < 0.0 {
+            -1.0
+        } else {
+            1.0
+        };
+        sum += coords * weight * sign;
+    }
+
+    if sum.norm_squared() > f32::EPSILON {
+        Some(UnitQuaternion::from_quaternion(Quaternion::from(sum)))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::transform_system_bundle;
+
+    #[test]
+    fn did_constrain() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut resources = Resources::default();
+        let mut world = Universe::new().create_world();
+        let mut systems = transform_system_bundle::build(&mut world, &mut resources);
+
+        let sources = world.insert(
+            (),
+            vec![
+                (
+                    Translation::new(1.0, 2.0, 3.0),
+                    Rotation::from_euler_angles(0.0, 0.0, 1.0),
+                    LocalToWorld::identity(),
+                ),
+                (
+                    Translation::new(3.0, 2.0, 1.0),
+                    Rotation::from_euler_angles(0.0, 0.0, 1.0),
+                    LocalToWorld::identity(),
+                ),
+            ],
+        );
+        let (a, b) = (sources[0], sources[1]);
+
+        let position_constrained = *world
+            .insert(
+                (),
+                vec![(
+                    PositionConstraint::with(&[
+                        ConstraintSource::new(a, 1.0, Vector3::zeros()),
+                        ConstraintSource::new(b, 1.0, Vector3::zeros()),
+                    ]),
+                    LocalToWorld::identity(),
+                )],
+            )
+            .first()
+            .unwrap();
+
+        // Constrained to `a` as if it were a child of it, and constrained to by `position_constrained`
+        // (which it then depends on).
+        let parent_constrained = *world
+            .insert(
+                (),
+                vec![(
+                    ParentConstraint::with(&[ConstraintSource::new(a, 1.0, Matrix4::identity())]),
+                    PositionConstraint::with(&[ConstraintSource::new(
+                        position_constrained,
+                        1.0,
+                        Vector3::new(0.0, 1.0, 0.0),
+                    )]),
+                    Translation::new(1.0, 0.0, 0.0),
+                    LocalToWorld::identity(),
+                )],
+            )
+            .first()
+            .unwrap();
+
+        // A real child of the constrained entity follows it.
+        let child = *world
+            .insert(
+                (),
+                vec![(
+                    Translation::new(0.0, 0.0, 1.0),
+                    Parent(parent_constrained),
+                    LocalToParent::identity(),
+                    LocalToWorld::identity(),
+                )],
+            )
+            .first()
+            .unwrap();
+
+        for _ in 0..2 {
+            for system in systems.iter_mut() {
+                system.run(&mut world, &mut resources);
+                system
+                    .command_buffer_mut(world.id())
+                    .unwrap()
+                    .write(&mut world);
+            }
+        }
+
+        let position = world
+            .get_component::<LocalToWorld>(position_constrained)
+            .unwrap()
+            .position();
+        assert!((position - Point3::new(2.0, 2.0, 2.0)).norm() < 1e-4);
+
+        // Rotation comes from `a` (through the parent constraint), position from the position
+        // constraint.
+        let local_to_world = *world
+            .get_component::<LocalToWorld>(parent_constrained)
+            .unwrap();
+        assert!((local_to_world.position() - Point3::new(2.0, 3.0, 2.0)).norm() < 1e-4);
+        assert!(
+            local_to_world
+                .rotation()
+                .angle_to(&Rotation::from_euler_angles(0.0, 0.0, 1.0))
+                < 1e-4
+        );
+
+        let child_position = world
+            .get_component::<LocalToWorld>(child)
+            .unwrap()
+            .position();
+        assert!((child_position - Point3::new(2.0, 3.0, 3.0)).norm() < 1e-4);
+    }
+
+    #[test]
+    fn did_constrain_parent_and_child() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut resources = Resources::default();
+        let mut world = Universe::new().create_world();
+        let mut systems = transform_system_bundle::build(&mut world, &mut resources);
+
+        let source = *world
+            .insert(
+                (),
+                vec![(Translation::new(5.0, 0.0, 0.0), LocalToWorld::identity())],
+            )
+            .first()
+            .unwrap();
+        let parent = *world
+            .insert(
+                (),
+                vec![(Translation::identity(), LocalToWorld::identity())],
+            )
+            .first()
+            .unwrap();
+        let child = *world
+            .insert(
+                (),
+                vec![(
+                    PositionConstraint::with(&[ConstraintSource::new(
+                        source,
+                        1.0,
+                        Vector3::new(0.0, 1.0, 0.0),
+                    )]),
+                    Translation::new(0.0, 0.0, 1.0),
+                    Parent(parent),
+                    LocalToParent::identity(),
+                    LocalToWorld::identity(),
+                )],
+            )
+            .first()
+            .unwrap();
+        // Constrained after its child, so the child comes first in query order.
+        world
+            .add_component(
+                parent,
+                PositionConstraint::with(&[ConstraintSource::new(
+                    source,
+                    1.0,
+                    Vector3::new(0.0, 0.0, 2.0),
+                )]),
+            )
+            .unwrap();
+
+        for _ in 0..2 {
+            for system in systems.iter_mut() {
+                system.run(&mut world, &mut resources);
+                system
+                    .command_buffer_mut(world.id())
+                    .unwrap()
+                    .write(&mut world);
+            }
+        }
+
+        // The parent is evaluated first, so re-propagating it doesn't undo the child's constraint.
+        let parent_position = world
+            .get_component::<LocalToWorld>(parent)
+            .unwrap()
+            .position();
+        assert!((parent_position - Point3::new(5.0, 0.0, 2.0)).norm() < 1e-4);
+        let child_position = world
+            .get_component::<LocalToWorld>(child)
+            .unwrap()
+            .position();
+        assert!((child_position - Point3::new(5.0, 1.0, 0.0)).norm() < 1e-4);
+    }
+}
@@ -0,0 +1,145 @@
+#![allow(dead_code)]
+use crate::{
+    components::*,
+    ecs::{prelude::*, systems::SubWorld},
+    inheritance_system,
+    local_transform::LocalTransformContribution,
+    local_transform_system,
+};
+
+/// Builds the systems maintaining `DisabledInHierarchy`: an `inheritance_system` for `Disabled`,
+/// then one that marks every entity with an `Inherited<Disabled>`.
+pub fn build(world: &mut World, resources: &mut Resources) -> Vec<Box<dyn Schedulable>> {
+    let disabled_inheritance_system = inheritance_system::build::<Disabled>(world, resources);
+
+    let (contributions, _) = local_transform_system::contributions(resources);
+    let builder = SystemBuilder::<()>::new("DisabledHierarchySystem");
+    let builder = local_transform_system::declare_compose_access(&contributions, builder);
+    let disabled_hierarchy_system = builder
+        // Entities that became disabled
+        .with_query(
+            <Read<Inherited<Disabled>>>::query().filter(!component::<DisabledInHierarchy>()),
+        )
+        // Entities that became enabled
+        .with_query(
+            <Read<DisabledInHierarchy>>::query().filter(!component::<Inherited<Disabled>>()),
+        )
+        .read_component::<Parent>()
+        .write_component::<LocalToParent>()
+        .write_component::<LocalToWorld>()
+        .build(move |commands, world, _resource, queries| {
+            let (disabled, enabled) = queries;
+
+            for (entity, _) in disabled.iter_entities(world) {
+                log::trace!("Disabling {}", entity);
+                commands.add_component(entity, DisabledInHierarchy);
+            }
+
+            let enabled = enabled
+                .iter_entities(world)
+                .map(|(entity, _)| entity)
+                .collect::<Vec<_>>();
+            for entity in enabled {
+                log::trace!("Enabling {}", entity);
+                commands.remove_component::<DisabledInHierarchy>(entity);
+                catch_up(world, &contributions, entity);
+            }
+        });
+
+    vec![disabled_inheritance_system, disabled_hierarchy_system]
+}
+
+/// Recomputes the local transform of an entity that is being enabled again. The
+/// `LocalTransformUpdateSystem` only composes changed components, and changes made while the entity
+/// was disabled were filtered out, so they would otherwise never be picked up. This is written
+/// while `DisabledInHierarchy` is still present, which is fine: nothing reads the local transform
+/// of a disabled entity, and once the removal is flushed propagation recomputes the `LocalToWorld`
+/// of the entity and its subtree from it.
+fn catch_up(world: &mut SubWorld, contributions: &[LocalTransformContribution], entity: Entity) {
+    let local = match local_transform_system::compose_entity(contributions, world, entity) {
+        Some(local) => local,
+        None => return,
+    };
+
+    if world.get_component::<Parent>(entity).is_some() {
+        if let Some(mut local_to_parent) = world.get_component_mut::<LocalToParent>(entity) {
+            *local_to_parent = LocalToParent(local);
+        }
+    } else if let Some(mut local_to_world) = world.get_component_mut::<LocalToWorld>(entity) {
+        *local_to_world = LocalToWorld(local);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{math::Vector3, transform_system_bundle};
+
+    #[test]
+    fn did_skip_disabled_subtree() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut resources = Resources::default();
+        let mut world = Universe::new().create_world();
+        let mut systems = transform_system_bundle::build(&mut world, &mut resources);
+
+        let parent = *world
+            .insert(
+                (),
+                vec![(Translation::new(1.0, 0.0, 0.0), LocalToWorld::identity())],
+            )
+            .first()
+            .unwrap();
+        let child = *world
+            .insert(
+                (),
+                vec![(
+                    Translation::new(0.0, 2.0, 0.0),
+                    Parent(parent),
+                    LocalToParent::identity(),
+                    LocalToWorld::identity(),
+                )],
+            )
+            .first()
+            .unwrap();
+
+        let mut run = |world: &mut World, resources: &mut Resources| {
+            for system in systems.iter_mut() {
+                system.run(world, resources);
+                system.command_buffer_mut(world.id()).unwrap().write(world);
+            }
+        };
+        run(&mut world, &mut resources);
+        run(&mut world, &mut resources);
+
+        let position = |world: &World, entity| {
+            world
+                .get_component::<LocalToWorld>(entity)
+                .unwrap()
+                .position()
+                .coords
+        };
+        assert!((position(&world, child) - Vector3::new(1.0, 2.0, 0.0)).norm() < 1.0e-6);
+
+        // Nothing in a disabled subtree is updated.
+        world.add_component(parent, Disabled).unwrap();
+        run(&mut world, &mut resources);
+        *world.get_component_mut::<Translation>(parent).unwrap() = Translation::new(5.0, 0.0, 0.0);
+        *world.get_component_mut::<Translation>(child).unwrap() = Translation::new(0.0, 3.0, 0.0);
+        run(&mut world, &mut resources);
+        run(&mut world, &mut resources);
+
+        assert!(world.get_component::<DisabledInHierarchy>(child).is_some());
+        assert!((position(&world, parent) - Vector3::new(1.0, 0.0, 0.0)).norm() < 1.0e-6);
+        assert!((position(&world, child) - Vector3::new(1.0, 2.0, 0.0)).norm() < 1.0e-6);
+
+        // Enabling it again catches up with the changes.
+        world.remove_component::<Disabled>(parent).unwrap();
+        run(&mut world, &mut resources);
+        run(&mut world, &mut resources);
+
+        assert!(world.get_component::<DisabledInHierarchy>(child).is_none());
+        assert!((position(&world, parent) - Vector3::new(5.0, 0.0, 0.0)).norm() < 1.0e-6);
+        assert!((position(&world, child) - Vector3::new(5.0, 3.0, 0.0)).norm() < 1.0e-6);
+    }
+}
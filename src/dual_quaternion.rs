@@ -0,0 +1,136 @@
+use crate::{
+    components::{Rotation, Translation},
+    math::{Isometry3, Point3, Quaternion, Translation3, UnitQuaternion, Vector3},
+};
+use std::{fmt, ops::Mul};
+
+/// A rigid transform (rotation followed by translation) stored as a dual quaternion, which can be
+/// blended without the volume loss of blending matrices. `real` holds the rotation and
+/// `dual = 0.5 * translation * real`.
+#[repr(C)]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct DualQuaternion {
+    pub real: Quaternion<f32>,
+    pub dual: Quaternion<f32>,
+}
+
+impl DualQuaternion {
+    #[inline(always)]
+    pub fn identity() -> Self {
+        Self {
+            real: Quaternion::identity(),
+            dual: Quaternion::new(0.0, 0.0, 0.0, 0.0),
+        }
+    }
+
+    pub fn from_rotation_translation(
+        rotation: &UnitQuaternion<f32>,
+        translation: &Vector3<f32>,
+    ) -> Self {
+        let real = rotation.into_inner();
+        Self {
+            real,
+            dual: Quaternion::from_parts(0.0, *translation) * real * 0.5,
+        }
+    }
+
+    #[inline(always)]
+    pub fn from_isometry(isometry: &Isometry3<f32>) -> Self {
+        Self::from_rotation_translation(&isometry.rotation, &isometry.translation.vector)
+    }
+
+    #[inline(always)]
+    pub fn from_components(rotation: &Rotation, translation: &Translation) -> Self {
+        Self::from_rotation_translation(&rotation.0, &translation.vector)
+    }
+
+    #[inline(always)]
+    pub fn rotation(&self) -> UnitQuaternion<f32> {
+        UnitQuaternion::from_quaternion(self.real)
+    }
+
+    pub fn translation(&self) -> Vector3<f32> {
+        (self.dual * self.real.conjugate()).imag() * (2.0 / self.real.norm_squared())
+    }
+
+    #[inline(always)]
+    pub fn to_isometry(&self) -> Isometry3<f32> {
+        Isometry3::from_parts(Translation3::from(self.translation()), self.rotation())
+    }
+
+    #[inline(always)]
+    pub fn to_components(&self) -> (Rotation, Translation) {
+        (
+            Rotation(self.rotation()),
+            Translation(Translation3::from(self.translation())),
+        )
+    }
+
+    /// Scales the dual quaternion so that `real` is a unit quaternion.
+    pub fn normalize(&self) -> Self {
+        let norm = self.real.norm();
+        Self {
+            real: self.real / norm,
+            dual: self.dual / norm,
+        }
+    }
+
+    #[inline(always)]
+    pub fn transform_point(&self, point: &Point3<f32>) -> Point3<f32> {
+        self.to_isometry().transform_point(point)
+    }
+
+    /// Dual quaternion linear blending, taking the shortest path to the first transform.
+    pub fn blend(transforms: &[(f32, DualQuaternion)]) -> Self {
+        let pivot = match transforms.first() {
+            Some((_, pivot)) => pivot.real,
+            None => return Self::identity(),
+        };
+        let zero = Quaternion::new(0.0, 0.0, 0.0, 0.0);
+        let (real, dual) =
+            transforms
+                .iter()
+                .fold((zero, zero), |(real, dual), (weight, transform)| {
+                    let weight = if transform.real.dot(&pivot) < 0.0 {
+                        -weight
+                    } else {
+                        *weight
+                    };
+                    (
+                        real + transform.real * weight,
+                        dual + transform.dual * weight,
+                    )
+                });
+        Self { real, dual }.normalize()
+    }
+}
+
+impl Default for DualQuaternion {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl Mul for DualQuaternion {
+    type Output = DualQuaternion;
+
+    /// Composes both transforms, `rhs` being applied first.
+    fn mul(self, rhs: DualQuaternion) -> DualQuaternion {
+        DualQuaternion {
+            real: self.real * rhs.real,
+            dual: self.real * rhs.dual + self.dual * rhs.real,
+        }
+    }
+}
+
+impl From<Isometry3<f32>> for DualQuaternion {
+    fn from(isometry: Isometry3<f32>) -> Self {
+        Self::from_isometry(&isometry)
+    }
+}
+
+impl fmt::Display for DualQuaternion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "DualQuaternion({}, {})", self.real, self.dual)
+    }
+}
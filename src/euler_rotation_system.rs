@@ -0,0 +1,122 @@
+#![allow(dead_code)]
+use crate::{components::*, ecs::prelude::*};
+
+pub fn build(_: &mut World, _: &mut Resources) -> Box<dyn Schedulable> {
+    SystemBuilder::<()>::new("EulerRotationSystem")
+        // Entities with a changed `EulerRotation`
+        .with_query(
+            <(Read<EulerRotation>, Write<Rotation>)>::query().filter(changed::<EulerRotation>()),
+        )
+        // Entities with an `EulerRotation` but no `Rotation` to write it to yet
+        .with_query(<Read<EulerRotation>>::query().filter(!component::<Rotation>()))
+        .build(move |commands, world, _resource, queries| {
+            for (euler_rotation, mut rotation) in queries.0.iter_mut(world) {
+                *rotation = Rotation::from(*euler_rotation);
+            }
+
+            for (entity, euler_rotation) in queries.1.iter_entities(world) {
+                log::trace!("Adding missing Rotation to {}", entity);
+                commands.add_component(entity, Rotation::from(*euler_rotation));
+            }
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::math::Vector3;
+
+    #[test]
+    fn correct_rotation() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut resources = Resources::default();
+        let mut world = Universe::new().create_world();
+        let mut system = build(&mut world, &mut resources);
+
+        let euler = EulerRotation::degrees(10.0, 20.0, 30.0, RotationOrder::Xyz);
+        let with_rotation = *world
+            .insert((), vec![(euler, Rotation::identity())])
+            .first()
+            .unwrap();
+        let without_rotation = *world.insert((), vec![(euler,)]).first().unwrap();
+
+        system.run(&mut world, &mut resources);
+        system
+            .command_buffer_mut(world.id())
+            .unwrap()
+            .write(&mut world);
+
+        let expected = Rotation::from_euler_angles(
+            10.0f32.to_radians(),
+            20.0f32.to_radians(),
+            30.0f32.to_radians(),
+        );
+        for entity in [with_rotation, without_rotation].iter() {
+            let rotation = *world.get_component::<Rotation>(*entity).unwrap();
+            assert!(rotation.angle_to(&expected) < 1e-5);
+        }
+
+        // And back again, in the order and unit of the hint.
+        let decomposed = EulerRotation::from_quaternion_with_hint(&expected, &euler);
+        assert!((decomposed.angles - euler.angles).norm() < 1e-3);
+    }
+
+    const ORDERS: [RotationOrder; 6] = [
+        RotationOrder::Xyz,
+        RotationOrder::Xzy,
+        RotationOrder::Yxz,
+        RotationOrder::Yzx,
+        RotationOrder::Zxy,
+        RotationOrder::Zyx,
+    ];
+
+    #[test]
+    fn round_trip_every_order() {
+        for &order in ORDERS.iter() {
+            let euler = EulerRotation::degrees(10.0, -35.0, 60.0, order);
+            let rotation = euler.to_quaternion();
+
+            let decomposed = EulerRotation::from_quaternion(&rotation, order, AngleUnit::Degrees);
+            assert!(
+                (decomposed.angles - euler.angles).norm() < 1e-3,
+                "{:?}: {}",
+                order,
+                decomposed
+            );
+            assert!(decomposed.to_quaternion().angle_to(&rotation) < 1e-5);
+        }
+    }
+
+    #[test]
+    fn round_trip_gimbal_lock() {
+        for &order in ORDERS.iter() {
+            let [first, second, third] = order.axes();
+            for &middle in [90.0, -90.0].iter() {
+                let mut angles = Vector3::zeros();
+                angles[first] = 25.0;
+                angles[second] = middle;
+                angles[third] = -40.0;
+                let euler =
+                    EulerRotation::new(angles.x, angles.y, angles.z, order, AngleUnit::Degrees);
+                let rotation = euler.to_quaternion();
+
+                // Only the combination of the first and last angle is known, so compare rotations.
+                let decomposed =
+                    EulerRotation::from_quaternion(&rotation, order, AngleUnit::Degrees);
+                assert!((decomposed.angles[second] - middle).abs() < 1e-1);
+                assert!(decomposed.to_quaternion().angle_to(&rotation) < 1e-3);
+
+                // With a hint the first angle is pinned, which recovers the original angles.
+                let hinted = EulerRotation::from_quaternion_with_hint(&rotation, &euler);
+                assert!(
+                    (hinted.angles - euler.angles).norm() < 1e-1,
+                    "{:?} {}: {}",
+                    order,
+                    middle,
+                    hinted
+                );
+            }
+        }
+    }
+}
@@ -1,9 +1,24 @@
 #![allow(dead_code)]
-use crate::{components::*, ecs::prelude::*};
+use crate::{
+    components::*,
+    ecs::prelude::*,
+    hierarchy_order::HierarchyOrder,
+    local_to_world_propagate_system::{HierarchyDepthTags, PropagationMode},
+};
 use smallvec::SmallVec;
 use std::collections::HashMap;
 
-pub fn build(_: &mut World, _: &mut Resources) -> Vec<Box<dyn Schedulable>> {
+pub fn build(_: &mut World, resources: &mut Resources) -> Vec<Box<dyn Schedulable>> {
+    if !resources.contains::<HierarchyOrder>() {
+        resources.insert(HierarchyOrder::default());
+    }
+    if !resources.contains::<PropagationMode>() {
+        resources.insert(PropagationMode::default());
+    }
+    if !resources.contains::<HierarchyDepthTags>() {
+        resources.insert(HierarchyDepthTags::default());
+    }
+
     let missing_previous_parent_system = SystemBuilder::<()>::new("MissingPreviousParentSystem")
         // Entities with missing `PreviousParent`
         .with_query(<Read<Parent>>::query().filter(
@@ -20,6 +35,7 @@ pub fn build(_: &mut World, _: &mut Resources) -> Vec<Box<dyn Schedulable>> {
         });
 
     let parent_update_system = SystemBuilder::<()>::new("ParentUpdateSystem")
+        .write_resource::<HierarchyOrder>()
         // Entities with a removed `Parent`
         .with_query(<Read<PreviousParent>>::query().filter(!component::<Parent>()))
         // Entities with a changed `Parent`
@@ -29,7 +45,7 @@ pub fn build(_: &mut World, _: &mut Resources) -> Vec<Box<dyn Schedulable>> {
         // Deleted Parents (ie Entities with `Children` and without a `LocalToWorld`).
         .with_query(<Read<Children>>::query().filter(!component::<LocalToWorld>()))
         .write_component::<Children>()
-        .build(move |commands, world, _resource, queries| {
+        .build(move |commands, world, order, queries| {
             // Entities with a missing `Parent` (ie. ones that have a `PreviousParent`), remove
             // them from the `Children` of the `PreviousParent`.
             for (entity, previous_parent) in queries.0.iter_entities(world) {
@@ -39,7 +55,11 @@ pub fn build(_: &mut World, _: &mut Resources) -> Vec<Box<dyn Schedulable>> {
                         world.get_component_mut::<Children>(previous_parent_entity)
                     {
                         log::trace!(" > Removing {} from it's prev parent's children", entity);
+                        let count = previous_parent_children.0.len();
                         previous_parent_children.0.retain(|e| *e != entity);
+                        if previous_parent_children.0.len() != count {
+                            order.mark_dirty();
+                        }
                     }
                 }
             }
@@ -69,6 +89,8 @@ pub fn build(_: &mut World, _: &mut Resources) -> Vec<Box<dyn Schedulable>> {
                     }
                 }
 
+                order.mark_dirty();
+
                 // Set `PreviousParent = Parent`.
                 *previous_parent = PreviousParent(Some(parent.0));
 
@@ -101,6 +123,7 @@ pub fn build(_: &mut World, _: &mut Resources) -> Vec<Box<dyn Schedulable>> {
                 log::trace!("The entity {} doesn't have a LocalToWorld", entity);
                 if children_additions.remove(&entity).is_none() {
                     log::trace!(" > It needs to be remove from the ECS.");
+                    order.mark_dirty();
                     for child_entity in children.0.iter() {
                         commands.remove_component::<Parent>(*child_entity);
                         commands.remove_component::<PreviousParent>(*child_entity);
@@ -121,7 +144,72 @@ pub fn build(_: &mut World, _: &mut Resources) -> Vec<Box<dyn Schedulable>> {
             });
         });
 
-    vec![missing_previous_parent_system, parent_update_system]
+    // The depth each entity was last tagged with.
+    let mut depths = HashMap::<Entity, u32>::new();
+    let hierarchy_depth_system = SystemBuilder::<()>::new("HierarchyDepthSystem")
+        .read_resource::<PropagationMode>()
+        .write_resource::<HierarchyDepthTags>()
+        // Roots of a hierarchy
+        .with_query(<Read<Children>>::query().filter(!component::<Parent>()))
+        // Entities with a changed `Parent`
+        .with_query(<Read<Parent>>::query().filter(changed::<Parent>()))
+        // Changed children
+        .with_query(<Read<Children>>::query().filter(changed::<Children>()))
+        .read_component::<Children>()
+        .build(move |commands, world, (mode, tags), queries| {
+            let (roots, changed_parents, changed_children) = queries;
+            // The commands of the previous run have been flushed by now.
+            tags.pending = false;
+
+            if **mode != PropagationMode::ByDepth {
+                // Merge the chunks of every depth back together.
+                for (entity, _) in depths.drain() {
+                    commands.remove_tag::<HierarchyDepth>(entity);
+                }
+                return;
+            }
+
+            let changed = changed_parents.iter(world).next().is_some()
+                || changed_children.iter(world).next().is_some();
+            if !changed && !depths.is_empty() {
+                return;
+            }
+
+            let mut stack = roots
+                .iter_entities(world)
+                .map(|(entity, _)| (entity, 0))
+                .collect::<Vec<_>>();
+            let mut new_depths = HashMap::with_capacity(depths.len());
+            while let Some((entity, depth)) = stack.pop() {
+                if depth > 0 {
+                    new_depths.insert(entity, depth);
+                }
+                if let Some(children) = world.get_component::<Children>(entity) {
+                    stack.extend(children.iter().map(|child| (*child, depth + 1)));
+                }
+            }
+
+            for (entity, depth) in new_depths.iter() {
+                if depths.get(entity) != Some(depth) {
+                    log::trace!("Tagging {} with depth {}", entity, depth);
+                    tags.pending = true;
+                    commands.add_tag(*entity, HierarchyDepth(*depth));
+                }
+            }
+            for entity in depths.keys() {
+                if !new_depths.contains_key(entity) {
+                    tags.pending = true;
+                    commands.remove_tag::<HierarchyDepth>(*entity);
+                }
+            }
+            depths = new_depths;
+        });
+
+    vec![
+        missing_previous_parent_system,
+        parent_update_system,
+        hierarchy_depth_system,
+    ]
 }
 
 #[cfg(test)]
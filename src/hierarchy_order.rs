@@ -0,0 +1,108 @@
+use crate::ecs::prelude::*;
+use std::collections::HashMap;
+
+/// An entity in the `HierarchyOrder`, along with the index of its parent's entry.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct HierarchyEntry {
+    pub entity: Entity,
+    /// `None` for the roots of hierarchies.
+    pub parent: Option<usize>,
+}
+
+/// Every hierarchy flattened into a single array, sorted by depth so that parents always come
+/// before their children. This is a resource maintained by the `LocalToWorldPropagateSystem`, which
+/// rebuilds it whenever it is marked dirty (the `HierarchyMaintenanceSystem` does so on every
+/// structural change) instead of collecting `Children` on every frame. The index of each entity is
+/// cached as well, so that propagation can gather components into arrays laid out like the entries
+/// with one pass over their chunks, then sweep the arrays. Other systems can read it to walk
+/// hierarchies in the same order.
+#[derive(Debug, Clone)]
+pub struct HierarchyOrder {
+    entries: Vec<HierarchyEntry>,
+    indices: HashMap<Entity, usize>,
+    dirty: bool,
+}
+
+impl HierarchyOrder {
+    #[inline(always)]
+    pub fn entries(&self) -> &[HierarchyEntry] {
+        &self.entries
+    }
+
+    /// The index of the entry of `entity`, if it is part of a hierarchy.
+    #[inline(always)]
+    pub fn index_of(&self, entity: Entity) -> Option<usize> {
+        self.indices.get(&entity).cloned()
+    }
+
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    #[inline(always)]
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Requests a rebuild before the next propagation, for systems that change `Parent` or
+    /// `Children` without going through the `HierarchyMaintenanceSystem`.
+    #[inline(always)]
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    /// Rebuilds the order from the roots of every hierarchy, breadth first.
+    pub fn rebuild<F>(&mut self, roots: impl IntoIterator<Item = Entity>, mut children_of: F)
+    where
+        F: FnMut(Entity) -> Vec<Entity>,
+    {
+        self.entries.clear();
+        self.entries
+            .extend(roots.into_iter().map(|entity| HierarchyEntry {
+                entity,
+                parent: None,
+            }));
+
+        let mut index = 0;
+        while index < self.entries.len() {
+            let entity = self.entries[index].entity;
+            for child in children_of(entity) {
+                self.entries.push(HierarchyEntry {
+                    entity: child,
+                    parent: Some(index),
+                });
+            }
+            index += 1;
+        }
+
+        self.indices.clear();
+        self.indices.extend(
+            self.entries
+                .iter()
+                .enumerate()
+                .map(|(index, entry)| (entry.entity, index)),
+        );
+
+        log::trace!(
+            "Rebuilt the hierarchy order ({} entities)",
+            self.entries.len()
+        );
+        self.dirty = false;
+    }
+}
+
+impl Default for HierarchyOrder {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+            indices: HashMap::new(),
+            dirty: true,
+        }
+    }
+}
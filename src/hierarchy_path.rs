@@ -0,0 +1,118 @@
+use crate::{
+    components::*,
+    ecs::{prelude::*, systems::SubWorld},
+};
+use smallvec::SmallVec;
+use std::collections::HashMap;
+
+/// Read access to the hierarchy and `Name`s of entities, implemented for both `World` and
+/// `SubWorld` so paths can be resolved from systems as well as from tools and scripts. Systems need
+/// read access to `Parent`, `Children` and `Name`.
+pub trait NamedHierarchy {
+    /// Calls `f` with the `T` of `entity`, if it has one. Everything else is built on this lookup.
+    fn with_component<T: Component, R>(&self, entity: Entity, f: impl FnOnce(&T) -> R)
+        -> Option<R>;
+
+    fn parent_of(&self, entity: Entity) -> Option<Entity> {
+        self.with_component(entity, |parent: &Parent| parent.0)
+    }
+
+    fn children_of(&self, entity: Entity) -> SmallVec<[Entity; 8]> {
+        self.with_component(entity, |children: &Children| children.0.clone())
+            .unwrap_or_default()
+    }
+
+    fn has_name(&self, entity: Entity, name: &str) -> bool {
+        self.with_component(entity, |entity_name: &Name| entity_name.0 == name)
+            .unwrap_or(false)
+    }
+
+    fn name_of(&self, entity: Entity) -> Option<String> {
+        self.with_component(entity, |name: &Name| name.0.clone())
+    }
+}
+
+impl NamedHierarchy for World {
+    fn with_component<T: Component, R>(
+        &self,
+        entity: Entity,
+        f: impl FnOnce(&T) -> R,
+    ) -> Option<R> {
+        self.get_component::<T>(entity)
+            .map(|component| f(&component))
+    }
+}
+
+impl NamedHierarchy for SubWorld {
+    fn with_component<T: Component, R>(
+        &self,
+        entity: Entity,
+        f: impl FnOnce(&T) -> R,
+    ) -> Option<R> {
+        self.get_component::<T>(entity)
+            .map(|component| f(&component))
+    }
+}
+
+/// Resolves a `/` separated path of `Name`s (eg. `"turret/barrel/muzzle"`) through `Children`,
+/// starting at `root`. An empty path resolves to `root` itself. When siblings share a name, the
+/// first one is used.
+pub fn find_by_path<W: NamedHierarchy>(world: &W, root: Entity, path: &str) -> Option<Entity> {
+    path.split('/')
+        .filter(|name| !name.is_empty())
+        .try_fold(root, |entity, name| {
+            world
+                .children_of(entity)
+                .into_iter()
+                .find(|child| world.has_name(*child, name))
+        })
+}
+
+/// The path of `entity` relative to `root`, the inverse of `find_by_path`. Returns `None` if `root`
+/// is not an ancestor of `entity`, or if an entity in between has no `Name`.
+pub fn path_of<W: NamedHierarchy>(world: &W, root: Entity, entity: Entity) -> Option<String> {
+    let mut names = Vec::new();
+    let mut current = entity;
+    while current != root {
+        names.push(world.name_of(current)?);
+        current = world.parent_of(current)?;
+    }
+    names.reverse();
+    Some(names.join("/"))
+}
+
+/// The path of every named descendant of `root`, as used by `NameIndex`. Unnamed entities and
+/// their descendants are left out, as they can't be addressed by path. When siblings share a name,
+/// the first one is kept, matching `find_by_path`.
+pub fn descendant_paths<W: NamedHierarchy>(world: &W, root: Entity) -> HashMap<String, Entity> {
+    let mut paths = HashMap::new();
+    let mut stack = world
+        .children_of(root)
+        .into_iter()
+        .rev()
+        .map(|child| (child, String::new()))
+        .collect::<Vec<_>>();
+    while let Some((entity, parent_path)) = stack.pop() {
+        let name = match world.name_of(entity) {
+            Some(name) => name,
+            None => continue,
+        };
+        let path = if parent_path.is_empty() {
+            name
+        } else {
+            format!("{}/{}", parent_path, name)
+        };
+        if paths.contains_key(&path) {
+            continue;
+        }
+        stack.extend(
+            world
+                .children_of(entity)
+                .into_iter()
+                .rev()
+                .map(|child| (child, path.clone())),
+        );
+        paths.insert(path, entity);
+    }
+    paths
+}
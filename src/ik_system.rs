@@ -0,0 +1,383 @@
+#![allow(dead_code)]
+use crate::{
+    components::*,
+    ecs::{prelude::*, systems::SubWorld},
+    local_to_world_propagate_system,
+    local_transform::LocalTransformContribution,
+    local_transform_system, look_at_system,
+    math::{Matrix4, Point3, Unit, UnitQuaternion, Vector3},
+};
+
+pub fn build(_: &mut World, resources: &mut Resources) -> Box<dyn Schedulable> {
+    let (contributions, _) = local_transform_system::contributions(resources);
+    let builder = SystemBuilder::<()>::new("IkSystem");
+    let builder = local_transform_system::declare_compose_access(&contributions, builder);
+    local_to_world_propagate_system::declare_repropagate_access(builder)
+        // End effectors of IK chains
+        .with_query(
+            <Read<IkChain>>::query()
+                .filter(component::<LocalToWorld>() & !component::<StaticTransform>()),
+        )
+        .read_component::<JointLimit>()
+        .write_component::<Rotation>()
+        .write_component::<LocalToParent>()
+        .build(move |commands, world, _resource, query| {
+            let chains = query
+                .iter_entities(world)
+                .map(|(entity, chain)| (entity, *chain))
+                .collect::<Vec<_>>();
+
+            for (end_effector, chain) in chains {
+                solve(world, commands, &contributions, end_effector, &chain);
+            }
+        })
+}
+
+fn solve(
+    world: &mut SubWorld,
+    commands: &mut CommandBuffer,
+    contributions: &[LocalTransformContribution],
+    end_effector: Entity,
+    chain: &IkChain,
+) {
+    let target = match look_at_system::target_position(world, &chain.target) {
+        Some(target) => target,
+        None => {
+            log::warn!(
+                "IK chain of {} has a target that does not have a LocalToWorld",
+                end_effector
+            );
+            return;
+        }
+    };
+
+    let joint_count = match chain.solver {
+        IkSolver::TwoBone { .. } => 2,
+        IkSolver::Fabrik { joints, .. } => joints,
+    };
+    if joint_count == 0 {
+        log::warn!("IK chain of {} has no joints to rotate", end_effector);
+        return;
+    }
+
+    // Walk up from the end effector, then order the chain root first.
+    let mut entities = vec![end_effector];
+    while entities.len() <= joint_count {
+        match world.get_component::<Parent>(*entities.last().unwrap()) {
+            Some(parent) => entities.push(parent.0),
+            None => {
+                log::warn!(
+                    "IK chain of {} needs {} ancestors, but only has {}",
+                    end_effector,
+                    joint_count,
+                    entities.len() - 1
+                );
+                return;
+            }
+        }
+    }
+    entities.reverse();
+
+    let mut positions = Vec::with_capacity(entities.len());
+    let mut rotations = Vec::with_capacity(entities.len());
+    for entity in entities.iter() {
+        match world.get_component::<LocalToWorld>(*entity) {
+            Some(local_to_world) => {
+                positions.push(local_to_world.position());
+                rotations.push(local_to_world.rotation());
+            }
+            None => return,
+        }
+    }
+
+    let solved = match chain.solver {
+        IkSolver::TwoBone { pole } => {
+            let pole = pole.and_then(|pole| look_at_system::target_position(world, &pole));
+            solve_two_bone(&positions, &target, pole.as_ref())
+        }
+        IkSolver::Fabrik {
+            iterations,
+            tolerance,
+            ..
+        } => solve_fabrik(&positions, &target, iterations, tolerance),
+    };
+    let world_rotations = solved_rotations(&positions, &solved, &rotations);
+
+    // Convert to local rotations, parent first so that clamping a joint is seen by its children.
+    let chain_root = entities[0];
+    let parent_local_to_world = world
+        .get_component::<Parent>(chain_root)
+        .and_then(|parent| world.get_component::<LocalToWorld>(parent.0).map(|e| *e));
+    let mut parent_rotation = parent_local_to_world
+        .map(|parent_local_to_world| parent_local_to_world.rotation())
+        .unwrap_or_else(UnitQuaternion::identity);
+
+    let mut chain_root_local = None;
+    for (entity, world_rotation) in entities.iter().zip(world_rotations.iter()) {
+        let mut rotation = parent_rotation.inverse() * world_rotation;
+        if let Some(joint_limit) = world.get_component::<JointLimit>(*entity) {
+            rotation = joint_limit.clamp(&rotation);
+        }
+        parent_rotation *= rotation;
+
+        let local = write_rotation(world, commands, contributions, *entity, Rotation(rotation));
+        chain_root_local.get_or_insert(local);
+    }
+
+    // Re-propagate from the root of the chain.
+    let local = chain_root_local.unwrap();
+    let local_to_world = match parent_local_to_world {
+        Some(parent_local_to_world) => local_to_world_propagate_system::child_local_to_world(
+            world,
+            chain_root,
+            &parent_local_to_world,
+            &LocalToParent(local),
+        ),
+        None => LocalToWorld(local),
+    };
+    local_to_world_propagate_system::repropagate(world, chain_root, local_to_world);
+}
+
+/// Writes the `Rotation` of a joint along with its `LocalToParent`, returning the new local
+/// transform matrix.
+fn write_rotation(
+    world: &mut SubWorld,
+    commands: &mut CommandBuffer,
+    contributions: &[LocalTransformContribution],
+    entity: Entity,
+    rotation: Rotation,
+) -> Matrix4<f32> {
+    match world.get_component_mut::<Rotation>(entity) {
+        Some(mut current) => *current = rotation,
+        None => commands.add_component(entity, rotation),
+    }
+
+    let local =
+        local_transform_system::compose_entity_with(contributions, world, entity, &rotation);
+
+    if let Some(mut local_to_parent) = world.get_component_mut::<LocalToParent>(entity) {
+        *local_to_parent = LocalToParent(local);
+    }
+    local
+}
+
+/// Moves the middle and end joints of a two bone chain `[a, b, c]` so that `c` reaches `target` (or
+/// gets as close as the bone lengths allow), bending towards `pole` or the chain's current bend.
+fn solve_two_bone(
+    positions: &[Point3<f32>],
+    target: &Point3<f32>,
+    pole: Option<&Point3<f32>>,
+) -> Vec<Point3<f32>> {
+    let (a, b, c) = (positions[0], positions[1], positions[2]);
+    let upper_length = (b - a).norm();
+    let lower_length = (c - b).norm();
+
+    let to_target = target - a;
+    let distance = to_target.norm();
+    if distance < f32::EPSILON || upper_length < f32::EPSILON || lower_length < f32::EPSILON {
+        return positions.to_vec();
+    }
+    let direction = to_target / distance;
+    let distance = distance
+        .max((upper_length - lower_length).abs() + f32::EPSILON)
+        .min(upper_length + lower_length - f32::EPSILON);
+
+    // The plane the chain bends in contains the direction to the target and the pole.
+    let bend = pole.map(|pole| pole - a).unwrap_or(b - a);
+    let normal = Unit::try_new(direction.cross(&bend), f32::EPSILON)
+        .or_else(|| Unit::try_new(direction.cross(&Vector3::y()), f32::EPSILON))
+        .unwrap_or_else(|| Unit::new_normalize(direction.cross(&Vector3::x())));
+
+    // Law of cosines for the angle at `a` between the target and the upper bone.
+    let cos_angle = (upper_length * upper_length + distance * distance
+        - lower_length * lower_length)
+        / (2.0 * upper_length * distance);
+    let angle = cos_angle.max(-1.0).min(1.0).acos();
+
+    let upper_direction = UnitQuaternion::from_axis_angle(&normal, angle) * direction;
+    vec![
+        a,
+        a + upper_direction * upper_length,
+        a + direction * distance,
+    ]
+}
+
+/// FABRIK: alternately pulls the chain towards the target from the end and back to the root, until
+/// the end joint is within `tolerance` of the target.
+fn solve_fabrik(
+    positions: &[Point3<f32>],
+    target: &Point3<f32>,
+    iterations: usize,
+    tolerance: f32,
+) -> Vec<Point3<f32>> {
+    let lengths = positions
+        .windows(2)
+        .map(|bone| (bone[1] - bone[0]).norm())
+        .collect::<Vec<_>>();
+    let root = positions[0];
+    let mut solved = positions.to_vec();
+    let end = solved.len() - 1;
+
+    // Out of reach, stretch straight towards the target.
+    if (target - root).norm() >= lengths.iter().sum::<f32>() {
+        if let Some(direction) = (target - root).try_normalize(f32::EPSILON) {
+            for i in 0..end {
+                solved[i + 1] = solved[i] + direction * lengths[i];
+            }
+        }
+        return solved;
+    }
+
+    for _ in 0..iterations {
+        if (solved[end] - target).norm() <= tolerance {
+            break;
+        }
+
+        solved[end] = *target;
+        // Joints that coincide with their neighbour have no direction, so they stay put.
+        for i in (0..end).rev() {
+            if let Some(direction) = (solved[i] - solved[i + 1]).try_normalize(f32::EPSILON) {
+                solved[i] = solved[i + 1] + direction * lengths[i];
+            }
+        }
+
+        solved[0] = root;
+        for i in 0..end {
+            if let Some(direction) = (solved[i + 1] - solved[i]).try_normalize(f32::EPSILON) {
+                solved[i + 1] = solved[i] + direction * lengths[i];
+            }
+        }
+    }
+
+    solved
+}
+
+/// The new world rotation of each joint but the last, rotating each bone by the smallest rotation
+/// from its original to its solved direction.
+fn solved_rotations(
+    original: &[Point3<f32>],
+    solved: &[Point3<f32>],
+    rotations: &[UnitQuaternion<f32>],
+) -> Vec<UnitQuaternion<f32>> {
+    (0..original.len() - 1)
+        .map(|i| {
+            let delta = UnitQuaternion::rotation_between(
+                &(original[i + 1] - original[i]),
+                &(solved[i + 1] - solved[i]),
+            )
+            .unwrap_or_else(UnitQuaternion::identity);
+            delta * rotations[i]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::transform_system_bundle;
+
+    #[test]
+    fn did_reach_target() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut resources = Resources::default();
+        let mut world = Universe::new().create_world();
+        let mut systems = transform_system_bundle::build(&mut world, &mut resources);
+
+        let two_bone_target = Point3::new(1.0, 1.0, 0.0);
+        let fabrik_target = Point3::new(1.5, 1.5, 0.5);
+
+        // Two straight chains along +Y with unit length bones.
+        let mut end_effectors = vec![];
+        for chain in [
+            IkChain::two_bone(two_bone_target, Some(Point3::new(-1.0, 1.0, 0.0).into())),
+            IkChain::fabrik(fabrik_target, 3),
+        ]
+        .iter()
+        {
+            let joint_count = match chain.solver {
+                IkSolver::TwoBone { .. } => 2,
+                IkSolver::Fabrik { joints, .. } => joints,
+            };
+            let mut parent = *world
+                .insert((), vec![(Rotation::identity(), LocalToWorld::identity())])
+                .first()
+                .unwrap();
+            for _ in 0..joint_count {
+                parent = *world
+                    .insert(
+                        (),
+                        vec![(
+                            Translation::new(0.0, 1.0, 0.0),
+                            Rotation::identity(),
+                            Parent(parent),
+                            LocalToParent::identity(),
+                            LocalToWorld::identity(),
+                        )],
+                    )
+                    .first()
+                    .unwrap();
+            }
+            world.add_component(parent, *chain).unwrap();
+            end_effectors.push(parent);
+        }
+
+        for _ in 0..2 {
+            for system in systems.iter_mut() {
+                system.run(&mut world, &mut resources);
+                system
+                    .command_buffer_mut(world.id())
+                    .unwrap()
+                    .write(&mut world);
+            }
+        }
+
+        let two_bone_position = world
+            .get_component::<LocalToWorld>(end_effectors[0])
+            .unwrap()
+            .position();
+        assert!((two_bone_position - two_bone_target).norm() < 1e-4);
+
+        let fabrik_position = world
+            .get_component::<LocalToWorld>(end_effectors[1])
+            .unwrap()
+            .position();
+        assert!((fabrik_position - fabrik_target).norm() < 1e-2);
+    }
+
+    #[test]
+    fn ignores_chain_without_joints() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut resources = Resources::default();
+        let mut world = Universe::new().create_world();
+        let mut systems = transform_system_bundle::build(&mut world, &mut resources);
+
+        let end_effector = *world
+            .insert(
+                (),
+                vec![(
+                    Translation::new(0.0, 1.0, 0.0),
+                    Rotation::identity(),
+                    IkChain::fabrik(Point3::new(1.0, 0.0, 0.0), 0),
+                    LocalToWorld::identity(),
+                )],
+            )
+            .first()
+            .unwrap();
+
+        for system in systems.iter_mut() {
+            system.run(&mut world, &mut resources);
+            system
+                .command_buffer_mut(world.id())
+                .unwrap()
+                .write(&mut world);
+        }
+
+        let position = world
+            .get_component::<LocalToWorld>(end_effector)
+            .unwrap()
+            .position();
+        assert!((position - Point3::new(0.0, 1.0, 0.0)).norm() < 1e-4);
+    }
+}
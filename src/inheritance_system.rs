@@ -0,0 +1,306 @@
+#![allow(dead_code)]
+use crate::{
+    components::*,
+    ecs::{prelude::*, systems::SubWorld},
+};
+use std::collections::HashSet;
+
+/// Builds a system computing `Inherited<T>` for every entity that has a `T` or descends from one
+/// that does, walking `Children` the same way as the `LocalToWorldPropagateSystem`. Only the
+/// subtrees below a changed, added or removed `T` or a changed `Children` are walked again, and
+/// `Inherited<T>` is only written where its value differs, so `changed::<Inherited<T>>()` can be
+/// used downstream.
+pub fn build<T: Inheritable>(_: &mut World, _: &mut Resources) -> Box<dyn Schedulable> {
+    // Entities that had a `T` when last seen, to notice it being removed.
+    let mut owners = HashSet::<Entity>::new();
+
+    SystemBuilder::<()>::new(format!("InheritanceSystem<{}>", std::any::type_name::<T>()))
+        // Roots of a hierarchy that inherited a value
+        .with_query(<Read<Inherited<T>>>::query().filter(!component::<Parent>()))
+        // Changed values
+        .with_query(<Read<T>>::query().filter(changed::<T>()))
+        // Changed children
+        .with_query(<Read<Children>>::query().filter(changed::<Children>()))
+        .read_component::<T>()
+        .read_component::<Parent>()
+        .read_component::<Children>()
+        .write_component::<Inherited<T>>()
+        .build(move |commands, world, _resource, queries| {
+            let (inherited_roots, changed_values, changed_children) = queries;
+
+            let mut dirty = changed_values
+                .iter_entities(world)
+                .map(|(entity, _)| entity)
+                .collect::<HashSet<_>>();
+            owners.extend(dirty.iter().cloned());
+            dirty.extend(
+                changed_children
+                    .iter_entities(world)
+                    .map(|(entity, _)| entity),
+            );
+            // Roots that were detached from their parent, or lost their value.
+            dirty.extend(
+                inherited_roots
+                    .iter_entities(world)
+                    .filter(|(entity, inherited)| {
+                        world
+                            .get_component::<T>(*entity)
+                            .map_or(true, |value| *value != inherited.0)
+                    })
+                    .map(|(entity, _)| entity),
+            );
+            owners.retain(|entity| {
+                if world.get_component::<T>(*entity).is_some() {
+                    true
+                } else {
+                    log::trace!("The inherited value of {} was removed", entity);
+                    dirty.insert(*entity);
+                    false
+                }
+            });
+
+            // Walk each subtree once, from its top-most dirty entity.
+            let subtrees = dirty
+                .iter()
+                .filter(|entity| !has_dirty_ancestor(world, &dirty, **entity))
+                .cloned()
+                .collect::<Vec<_>>();
+            for entity in subtrees {
+                let parent_value = world.get_component::<Parent>(entity).and_then(|parent| {
+                    world
+                        .get_component::<Inherited<T>>(parent.0)
+                        .map(|value| value.0.clone())
+                });
+                propagate_recursive::<T>(parent_value.as_ref(), world, entity, commands);
+            }
+        })
+}
+
+/// Whether any ancestor of `entity` is in `dirty`, in which case its subtree is walked from there.
+fn has_dirty_ancestor(world: &SubWorld, dirty: &HashSet<Entity>, entity: Entity) -> bool {
+    let mut current = entity;
+    while let Some(parent) = world.get_component::<Parent>(current) {
+        current = parent.0;
+        if dirty.contains(&current) {
+            return true;
+        }
+    }
+    false
+}
+
+fn propagate_recursive<T: Inheritable>(
+    parent_value: Option<&T>,
+    world: &mut SubWorld,
+    entity: Entity,
+    commands: &mut CommandBuffer,
+) {
+    let local = world
+        .get_component::<T>(entity)
+        .map(|value| (*value).clone());
+    let value = match (local, parent_value) {
+        (Some(local), Some(parent)) => Some(local.inherit(parent)),
+        (Some(local), None) => Some(local),
+        (None, parent) => parent.cloned(),
+    };
+
+    match &value {
+        Some(value) => {
+            let changed = match world.get_component::<Inherited<T>>(entity) {
+                Some(current) => current.0 != *value,
+                None => {
+                    commands.add_component(entity, Inherited(value.clone()));
+                    false
+                }
+            };
+            if changed {
+                log::trace!("Updating Inherited value for {}", entity);
+                if let Some(mut current) = world.get_component_mut::<Inherited<T>>(entity) {
+                    current.0 = value.clone();
+                }
+            }
+        }
+        None => {
+            if world.get_component::<Inherited<T>>(entity).is_some() {
+                commands.remove_component::<Inherited<T>>(entity);
+            }
+        }
+    }
+
+    // Collect children
+    let children = world
+        .get_component::<Children>(entity)
+        .map(|e| e.0.iter().cloned().collect::<Vec<_>>())
+        .unwrap_or_default();
+
+    for child in children {
+        propagate_recursive(value.as_ref(), world, child, commands);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::hierarchy_maintenance_system;
+
+    #[derive(Debug, PartialEq, Clone, Copy)]
+    struct TimeScale(f32);
+
+    impl Inheritable for TimeScale {
+        fn inherit(&self, parent: &Self) -> Self {
+            TimeScale(self.0 * parent.0)
+        }
+    }
+
+    #[test]
+    fn did_inherit_values() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut resources = Resources::default();
+        let mut world = Universe::new().create_world();
+        let mut systems = hierarchy_maintenance_system::build(&mut world, &mut resources);
+        systems.push(build::<TimeScale>(&mut world, &mut resources));
+
+        let root = *world
+            .insert((), vec![(TimeScale(0.5), LocalToWorld::identity())])
+            .first()
+            .unwrap();
+        // A child without a value of its own, and a grandchild that slows down further.
+        let child = *world
+            .insert(
+                (),
+                vec![(
+                    Parent(root),
+                    LocalToParent::identity(),
+                    LocalToWorld::identity(),
+                )],
+            )
+            .first()
+            .unwrap();
+        let grandchild = *world
+            .insert(
+                (),
+                vec![(
+                    TimeScale(0.5),
+                    Parent(child),
+                    LocalToParent::identity(),
+                    LocalToWorld::identity(),
+                )],
+            )
+            .first()
+            .unwrap();
+
+        let mut run = |world: &mut World, resources: &mut Resources| {
+            for system in systems.iter_mut() {
+                system.run(world, resources);
+                system.command_buffer_mut(world.id()).unwrap().write(world);
+            }
+        };
+        run(&mut world, &mut resources);
+        run(&mut world, &mut resources);
+
+        assert_eq!(
+            *world.get_component::<Inherited<TimeScale>>(root).unwrap(),
+            Inherited(TimeScale(0.5))
+        );
+        assert_eq!(
+            *world.get_component::<Inherited<TimeScale>>(child).unwrap(),
+            Inherited(TimeScale(0.5))
+        );
+        assert_eq!(
+            *world
+                .get_component::<Inherited<TimeScale>>(grandchild)
+                .unwrap(),
+            Inherited(TimeScale(0.25))
+        );
+
+        // Changing the root's value flows down to every descendant.
+        *world.get_component_mut::<TimeScale>(root).unwrap() = TimeScale(2.0);
+        run(&mut world, &mut resources);
+
+        assert_eq!(
+            *world
+                .get_component::<Inherited<TimeScale>>(grandchild)
+                .unwrap(),
+            Inherited(TimeScale(1.0))
+        );
+    }
+
+    #[test]
+    fn did_inherit_inserted_and_removed_values() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut resources = Resources::default();
+        let mut world = Universe::new().create_world();
+        let mut systems = hierarchy_maintenance_system::build(&mut world, &mut resources);
+        systems.push(build::<TimeScale>(&mut world, &mut resources));
+
+        let root = *world
+            .insert((), vec![(TimeScale(0.5), LocalToWorld::identity())])
+            .first()
+            .unwrap();
+        let child = *world
+            .insert(
+                (),
+                vec![(
+                    Parent(root),
+                    LocalToParent::identity(),
+                    LocalToWorld::identity(),
+                )],
+            )
+            .first()
+            .unwrap();
+
+        let mut run = |world: &mut World, resources: &mut Resources| {
+            for system in systems.iter_mut() {
+                system.run(world, resources);
+                system.command_buffer_mut(world.id()).unwrap().write(world);
+            }
+        };
+        run(&mut world, &mut resources);
+        run(&mut world, &mut resources);
+
+        // A value inserted below an existing hierarchy combines with the inherited one.
+        let grandchild = *world
+            .insert(
+                (),
+                vec![(
+                    TimeScale(0.5),
+                    Parent(child),
+                    LocalToParent::identity(),
+                    LocalToWorld::identity(),
+                )],
+            )
+            .first()
+            .unwrap();
+        run(&mut world, &mut resources);
+        run(&mut world, &mut resources);
+
+        assert_eq!(
+            *world
+                .get_component::<Inherited<TimeScale>>(grandchild)
+                .unwrap(),
+            Inherited(TimeScale(0.25))
+        );
+
+        // Removing it falls back to the parent's value.
+        world.remove_component::<TimeScale>(grandchild).unwrap();
+        run(&mut world, &mut resources);
+
+        assert_eq!(
+            *world
+                .get_component::<Inherited<TimeScale>>(grandchild)
+                .unwrap(),
+            Inherited(TimeScale(0.5))
+        );
+
+        // Removing the only value removes every inherited one.
+        world.remove_component::<TimeScale>(root).unwrap();
+        run(&mut world, &mut resources);
+
+        for entity in [root, child, grandchild].iter() {
+            assert!(world
+                .get_component::<Inherited<TimeScale>>(*entity)
+                .is_none());
+        }
+    }
+}
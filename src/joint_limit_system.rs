@@ -0,0 +1,63 @@
+#![allow(dead_code)]
+use crate::{components::*, ecs::prelude::*};
+
+pub fn build(_: &mut World, _: &mut Resources) -> Box<dyn Schedulable> {
+    SystemBuilder::<()>::new("JointLimitSystem")
+        // Entities with a changed `Rotation` or `JointLimit`
+        .with_query(<(Read<JointLimit>, Read<Rotation>)>::query().filter(
+            !component::<StaticTransform>() & (changed::<Rotation>() | changed::<JointLimit>()),
+        ))
+        .write_component::<Rotation>()
+        .build(move |_commands, world, _resource, query| {
+            // Only write the rotations that are out of bounds, so that writing doesn't mark every
+            // limited joint as changed again for the next run.
+            let clamped = query
+                .iter_entities(world)
+                .filter_map(|(entity, (joint_limit, rotation))| {
+                    joint_limit
+                        .try_clamp(&rotation)
+                        .map(|clamped| (entity, Rotation(clamped)))
+                })
+                .collect::<Vec<_>>();
+
+            for (entity, clamped) in clamped {
+                if let Some(mut rotation) = world.get_component_mut::<Rotation>(entity) {
+                    *rotation = clamped;
+                }
+            }
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::math::{UnitQuaternion, Vector3};
+
+    #[test]
+    fn did_clamp_rotation() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut resources = Resources::default();
+        let mut world = Universe::new().create_world();
+        let mut system = build(&mut world, &mut resources);
+
+        let twist_axis = Vector3::x_axis();
+        let limit = JointLimit::swing_twist(twist_axis, 0.5, -0.3, 0.3);
+        let rotation = Rotation(
+            UnitQuaternion::from_axis_angle(&Vector3::y_axis(), 0.8)
+                * UnitQuaternion::from_axis_angle(&twist_axis, 1.2),
+        );
+        let entity = *world.insert((), vec![(limit, rotation)]).first().unwrap();
+
+        system.run(&mut world, &mut resources);
+        system
+            .command_buffer_mut(world.id())
+            .unwrap()
+            .write(&mut world);
+
+        let clamped = *world.get_component::<Rotation>(entity).unwrap();
+        let (swing, twist) = swing_twist(&clamped, &twist_axis);
+        assert!((swing.angle() - 0.5).abs() < 1e-5);
+        assert!((twist_angle(&twist, &twist_axis) - 0.3).abs() < 1e-5);
+    }
+}
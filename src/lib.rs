@@ -1,18 +1,65 @@
 pub use legion as ecs;
 pub use nalgebra as math;
 
+pub mod animation_blend_system;
+pub mod animation_clip;
+pub mod animation_sampling_system;
+pub mod billboard_system;
 pub mod components;
+pub mod constraint_system;
+pub mod disabled_hierarchy_system;
+pub mod dual_quaternion;
+pub mod euler_rotation_system;
 pub mod hierarchy_maintenance_system;
+pub mod hierarchy_order;
+pub mod hierarchy_path;
+pub mod ik_system;
+pub mod inheritance_system;
+pub mod joint_limit_system;
 pub mod local_to_parent_system;
 pub mod local_to_world_propagate_system;
 pub mod local_to_world_system;
+pub mod local_transform;
+pub mod local_transform_batch;
+pub mod local_transform_system;
+pub mod look_at_system;
+pub mod name_index_system;
+pub mod retarget_system;
+pub mod root_motion_system;
+pub mod skeleton_system;
+pub mod skinning_system;
+pub mod socket_system;
+pub mod static_bake_system;
 pub mod transform_system_bundle;
 
 pub mod prelude {
+    pub use crate::animation_blend_system;
+    pub use crate::animation_clip::*;
+    pub use crate::animation_sampling_system;
+    pub use crate::billboard_system;
     pub use crate::components::*;
+    pub use crate::constraint_system;
+    pub use crate::disabled_hierarchy_system;
+    pub use crate::dual_quaternion::DualQuaternion;
+    pub use crate::euler_rotation_system;
     pub use crate::hierarchy_maintenance_system;
+    pub use crate::hierarchy_order::{HierarchyEntry, HierarchyOrder};
+    pub use crate::hierarchy_path;
+    pub use crate::ik_system;
+    pub use crate::inheritance_system;
+    pub use crate::joint_limit_system;
     pub use crate::local_to_parent_system;
     pub use crate::local_to_world_propagate_system;
     pub use crate::local_to_world_system;
+    pub use crate::local_transform::{LocalTransformComponent, LocalTransformComponents};
+    pub use crate::local_transform_system;
+    pub use crate::look_at_system;
+    pub use crate::name_index_system;
+    pub use crate::retarget_system;
+    pub use crate::root_motion_system;
+    pub use crate::skeleton_system;
+    pub use crate::skinning_system;
+    pub use crate::socket_system;
+    pub use crate::static_bake_system;
     pub use crate::transform_system_bundle;
 }
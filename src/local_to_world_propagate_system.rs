@@ -2,54 +2,335 @@
 use crate::{
     components::*,
     ecs::{prelude::*, systems::SubWorld},
+    hierarchy_order::HierarchyOrder,
+    math::Matrix4,
 };
+use std::collections::HashMap;
+
+/// How the `LocalToWorldPropagateSystem` walks hierarchies.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum PropagationMode {
+    /// A single pass over the `HierarchyOrder`.
+    Ordered,
+    /// Every depth in turn, each one as a parallel iteration over its chunks. Children are tagged
+    /// with their `HierarchyDepth`, which fragments archetypes by depth but suits wide and shallow
+    /// hierarchies. Frames where the tags are being updated are propagated as `Ordered`.
+    ByDepth,
+}
+
+impl Default for PropagationMode {
+    fn default() -> Self {
+        PropagationMode::Ordered
+    }
+}
+
+/// Whether the `HierarchyDepth` tags may lag behind the hierarchy, maintained by the
+/// `HierarchyMaintenanceSystem`. Tags are applied through a command buffer, so they only match the
+/// hierarchy once that has been flushed, and `PropagationMode::ByDepth` falls back to the
+/// `HierarchyOrder` until then.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct HierarchyDepthTags {
+    pub pending: bool,
+}
+
+impl Default for HierarchyDepthTags {
+    fn default() -> Self {
+        Self { pending: true }
+    }
+}
+
+pub fn build(_: &mut World, resources: &mut Resources) -> Box<dyn Schedulable> {
+    if !resources.contains::<HierarchyOrder>() {
+        resources.insert(HierarchyOrder::default());
+    }
+    if !resources.contains::<PropagationMode>() {
+        resources.insert(PropagationMode::default());
+    }
+    if !resources.contains::<HierarchyDepthTags>() {
+        resources.insert(HierarchyDepthTags::default());
+    }
 
-pub fn build(_: &mut World, _: &mut Resources) -> Box<dyn Schedulable> {
     SystemBuilder::<()>::new("LocalToWorldPropagateSystem")
+        .read_resource::<PropagationMode>()
+        .write_resource::<HierarchyOrder>()
+        .read_resource::<HierarchyDepthTags>()
         // Entities with a `Children` and `LocalToWorld` but NOT a `Parent` (ie those that are
         // roots of a hierarchy).
         .with_query(<(Read<Children>, Read<LocalToWorld>)>::query().filter(!component::<Parent>()))
+        // Changed children, which the `HierarchyMaintenanceSystem` adds through its command buffer
+        .with_query(<Read<Children>>::query().filter(changed::<Children>()))
         .read_component::<Children>()
         .read_component::<LocalToParent>()
-        .build(move |commands, world, _resource, query| {
-            for (children, local_to_world) in query.iter(world) {
-                for child in children.0.iter() {
-                    propagate_recursive(*local_to_world, world, *child, commands);
-                }
+        .read_component::<Parent>()
+        .write_component::<LocalToWorld>()
+        .read_component::<ScaleCompensation>()
+        .read_component::<SocketAttachment>()
+        .read_component::<Sockets>()
+        .read_component::<DisabledInHierarchy>()
+        .build(move |commands, world, (mode, order, tags), queries| {
+            let (roots, changed_children) = queries;
+            if order.is_dirty() || changed_children.iter(world).next().is_some() {
+                let roots = roots
+                    .iter_entities(world)
+                    .map(|(entity, _)| entity)
+                    .collect::<Vec<_>>();
+                order.rebuild(roots, |entity| {
+                    world
+                        .get_component::<Children>(entity)
+                        .map(|e| e.0.iter().cloned().collect::<Vec<_>>())
+                        .unwrap_or_default()
+                });
+            }
+
+            match **mode {
+                PropagationMode::ByDepth if !tags.pending => propagate_by_depth(&order, world),
+                _ => propagate(&order, world, commands),
             }
         })
 }
 
-fn propagate_recursive(
-    parent_local_to_world: LocalToWorld,
-    world: &mut SubWorld,
-    entity: Entity,
-    commands: &mut CommandBuffer,
-) {
-    log::trace!("Updating LocalToWorld for {}", entity);
-    let local_to_parent = {
-        if let Some(local_to_parent) = world.get_component::<LocalToParent>(entity) {
-            *local_to_parent
+/// Propagates `LocalToWorld` in a single pass over the `HierarchyOrder`, where every parent has
+/// already been computed by the time its children are reached. The components are first gathered
+/// into arrays laid out like the order, one pass over their chunks each, so the pass itself only
+/// looks up the (rare) entities with a `SocketAttachment` or `ScaleCompensation`.
+fn propagate(order: &HierarchyOrder, world: &mut SubWorld, commands: &mut CommandBuffer) {
+    let len = order.len();
+    let mut local_to_parents: Vec<Option<LocalToParent>> = vec![None; len];
+    let mut local_to_worlds: Vec<Option<LocalToWorld>> = vec![None; len];
+    let mut disabled = vec![false; len];
+    let mut adjusted = vec![false; len];
+
+    for (entity, local_to_parent) in <Read<LocalToParent>>::query()
+        .filter(component::<Parent>())
+        .iter_entities(world)
+    {
+        if let Some(index) = order.index_of(entity) {
+            local_to_parents[index] = Some(*local_to_parent);
+        }
+    }
+    for (entity, local_to_world) in <Read<LocalToWorld>>::query()
+        .filter(!component::<Parent>())
+        .iter_entities(world)
+    {
+        if let Some(index) = order.index_of(entity) {
+            local_to_worlds[index] = Some(*local_to_world);
+        }
+    }
+    for (entity, _) in <Read<DisabledInHierarchy>>::query().iter_entities(world) {
+        if let Some(index) = order.index_of(entity) {
+            disabled[index] = true;
+        }
+    }
+    let compensated = <Read<ScaleCompensation>>::query()
+        .iter_entities(world)
+        .map(|(entity, _)| entity)
+        .collect::<Vec<_>>();
+    let attached = <Read<SocketAttachment>>::query()
+        .iter_entities(world)
+        .map(|(entity, _)| entity)
+        .collect::<Vec<_>>();
+    for entity in compensated.into_iter().chain(attached) {
+        if let Some(index) = order.index_of(entity) {
+            adjusted[index] = true;
+        }
+    }
+
+    for (index, entry) in order.entries().iter().enumerate() {
+        if disabled[index] {
+            log::trace!("Skipping the disabled subtree of {}", entry.entity);
+            local_to_worlds[index] = None;
+            continue;
+        }
+        let parent_local_to_world = match entry.parent.and_then(|parent| local_to_worlds[parent]) {
+            Some(parent_local_to_world) => parent_local_to_world,
+            None => continue,
+        };
+        let local_to_parent = match local_to_parents[index] {
+            Some(local_to_parent) => local_to_parent,
+            None => {
+                log::warn!(
+                    "Entity {} is a child in the hierarchy but does not have a LocalToParent",
+                    entry.entity
+                );
+                continue;
+            }
+        };
+
+        local_to_worlds[index] = Some(if adjusted[index] {
+            child_local_to_world(
+                world,
+                entry.entity,
+                &parent_local_to_world,
+                &local_to_parent,
+            )
         } else {
+            LocalToWorld(parent_local_to_world.0 * local_to_parent.0)
+        });
+    }
+
+    // Written directly, so that the systems running after propagation (and `repropagate`) see it in
+    // the same frame, even when scheduled.
+    let mut written = vec![false; len];
+    for (entity, mut local_to_world) in <Write<LocalToWorld>>::query()
+        .filter(component::<Parent>() & !component::<DisabledInHierarchy>())
+        .iter_entities_mut(world)
+    {
+        if let Some(index) = order.index_of(entity) {
+            if let Some(new_local_to_world) = local_to_worlds[index] {
+                *local_to_world = new_local_to_world;
+                written[index] = true;
+            }
+        }
+    }
+    for (index, entry) in order.entries().iter().enumerate() {
+        if let (Some(_), Some(local_to_world), false) =
+            (entry.parent, local_to_worlds[index], written[index])
+        {
+            commands.add_component(entry.entity, local_to_world);
+        }
+    }
+}
+
+/// Propagates `LocalToWorld` one `HierarchyDepth` at a time, until a depth has no entities. This
+/// relies on the tags matching the hierarchy (ie. `HierarchyDepthTags::pending` being false), as
+/// children are only reached through the tag of their depth. The parent matrices of each depth are
+/// read from a snapshot taken after the previous one, never from the chunks being written.
+fn propagate_by_depth(order: &HierarchyOrder, world: &mut SubWorld) {
+    let mut parents = order
+        .entries()
+        .iter()
+        .filter(|entry| entry.parent.is_none())
+        .filter_map(|entry| {
+            world
+                .get_component::<LocalToWorld>(entry.entity)
+                .map(|local_to_world| (entry.entity, *local_to_world))
+        })
+        .collect::<HashMap<_, _>>();
+
+    for depth in 1.. {
+        if parents.is_empty() {
+            break;
+        }
+
+        let query = <(Read<Parent>, Read<LocalToParent>, Write<LocalToWorld>)>::query()
+            .filter(tag_value(&HierarchyDepth(depth)) & !component::<DisabledInHierarchy>());
+        let world: &SubWorld = world;
+        let parents_ref = &parents;
+        // SAFETY: The `LocalToWorld`s of this depth are written from several threads, one chunk
+        // each, and nothing else accesses them meanwhile: the parent matrices come from the
+        // `parents` snapshot, and `child_local_to_world` only reads `Parent`, `SocketAttachment`,
+        // `Sockets` and `ScaleCompensation`, none of which this system writes.
+        unsafe {
+            query.par_for_each_chunk_unchecked(world, |mut chunk| {
+                for (entity, (parent, local_to_parent, mut local_to_world)) in
+                    chunk.iter_entities_mut()
+                {
+                    let parent_local_to_world = match parents_ref.get(&parent.0) {
+                        Some(parent_local_to_world) => parent_local_to_world,
+                        None => continue,
+                    };
+                    *local_to_world = child_local_to_world(
+                        world,
+                        entity,
+                        parent_local_to_world,
+                        &local_to_parent,
+                    );
+                }
+            });
+        }
+
+        // The parents of the next depth.
+        parents = <Read<LocalToWorld>>::query()
+            .filter(tag_value(&HierarchyDepth(depth)) & !component::<DisabledInHierarchy>())
+            .iter_entities(world)
+            .map(|(entity, local_to_world)| (entity, *local_to_world))
+            .collect();
+        log::trace!("Propagated depth {}", depth);
+    }
+}
+
+/// Computes the `LocalToWorld` of a child from that of its parent, honoring any
+/// `SocketAttachment` and `ScaleCompensation` on the child.
+pub fn child_local_to_world(
+    world: &SubWorld,
+    entity: Entity,
+    parent_local_to_world: &LocalToWorld,
+    local_to_parent: &LocalToParent,
+) -> LocalToWorld {
+    let parent_local_to_world = match socket_offset(world, entity) {
+        Some(offset) => LocalToWorld(parent_local_to_world.0 * offset),
+        None => *parent_local_to_world,
+    };
+
+    match world.get_component::<ScaleCompensation>(entity) {
+        Some(compensation) => {
+            LocalToWorld(compensation.compose(&parent_local_to_world.0, &local_to_parent.0))
+        }
+        None => LocalToWorld(parent_local_to_world.0 * local_to_parent.0),
+    }
+}
+
+/// The offset of the socket `entity` is attached to, if any. The attachment only applies once the
+/// `SocketSystem` has made its target the `Parent` of `entity`.
+fn socket_offset(world: &SubWorld, entity: Entity) -> Option<Matrix4<f32>> {
+    let attachment = world.get_component::<SocketAttachment>(entity)?;
+    if world.get_component::<Parent>(entity).map(|parent| parent.0) != Some(attachment.target) {
+        return None;
+    }
+    let sockets = world.get_component::<Sockets>(attachment.target)?;
+    match sockets.get(&attachment.socket) {
+        Some(socket) => Some(socket.offset),
+        None => {
             log::warn!(
-                "Entity {} is a child in the hierarchy but does not have a LocalToParent",
-                entity
+                "Entity {} is attached to a missing socket {:?} of {}",
+                entity,
+                attachment.socket,
+                attachment.target
             );
-            return;
+            None
         }
-    };
+    }
+}
 
-    let new_local_to_world = LocalToWorld(parent_local_to_world.0 * local_to_parent.0);
-    commands.add_component(entity, new_local_to_world);
+/// Declares the component access `repropagate` and `child_local_to_world` need, for the systems
+/// that call them.
+pub fn declare_repropagate_access(builder: SystemBuilder) -> SystemBuilder {
+    builder
+        .read_component::<Parent>()
+        .read_component::<Children>()
+        .read_component::<LocalToParent>()
+        .read_component::<ScaleCompensation>()
+        .read_component::<SocketAttachment>()
+        .read_component::<Sockets>()
+        .read_component::<DisabledInHierarchy>()
+        .write_component::<LocalToWorld>()
+}
+
+/// Sets the `LocalToWorld` of `entity` and immediately recomputes it for every descendant. This is
+/// for systems that run after propagation and need their changes reflected in the subtree in the
+/// same frame. It writes components directly, so the calling system must be built with
+/// `declare_repropagate_access`. Disabled subtrees are skipped.
+pub fn repropagate(world: &mut SubWorld, entity: Entity, local_to_world: LocalToWorld) {
+    if let Some(mut current) = world.get_component_mut::<LocalToWorld>(entity) {
+        *current = local_to_world;
+    }
 
-    // Collect children
     let children = world
         .get_component::<Children>(entity)
         .map(|e| e.0.iter().cloned().collect::<Vec<_>>())
         .unwrap_or_default();
 
     for child in children {
-        propagate_recursive(new_local_to_world, world, child, commands);
+        if world.get_component::<DisabledInHierarchy>(child).is_some() {
+            continue;
+        }
+        let local_to_parent = match world.get_component::<LocalToParent>(child) {
+            Some(local_to_parent) => *local_to_parent,
+            None => continue,
+        };
+        let child_local_to_world =
+            child_local_to_world(world, child, &local_to_world, &local_to_parent);
+        repropagate(world, child, child_local_to_world);
     }
 }
 
@@ -57,8 +338,11 @@ fn propagate_recursive(
 mod test {
     use super::*;
     use crate::{
-        hierarchy_maintenance_system, local_to_parent_system, local_to_world_propagate_system,
-        local_to_world_system,
+        hierarchy_maintenance_system,
+        hierarchy_order::HierarchyEntry,
+        local_to_world_propagate_system, local_transform_system,
+        math::{Vector3, U3},
+        transform_system_bundle,
     };
 
     #[test]
@@ -70,8 +354,7 @@ mod test {
 
         let mut hierarchy_maintenance_systems =
             hierarchy_maintenance_system::build(&mut world, &mut resources);
-        let mut local_to_parent_system = local_to_parent_system::build(&mut world, &mut resources);
-        let mut local_to_world_system = local_to_world_system::build(&mut world, &mut resources);
+        let mut local_transform_system = local_transform_system::build(&mut world, &mut resources);
         let mut local_to_world_propagate_system =
             local_to_world_propagate_system::build(&mut world, &mut resources);
 
@@ -113,13 +396,8 @@ mod test {
                 .unwrap()
                 .write(&mut world);
         }
-        local_to_parent_system.run(&mut world, &mut resources);
-        local_to_parent_system
-            .command_buffer_mut(world.id())
-            .unwrap()
-            .write(&mut world);
-        local_to_world_system.run(&mut world, &mut resources);
-        local_to_world_system
+        local_transform_system.run(&mut world, &mut resources);
+        local_transform_system
             .command_buffer_mut(world.id())
             .unwrap()
             .write(&mut world);
@@ -140,5 +418,209 @@ mod test {
             Translation::new(1.0, 0.0, 0.0).to_homogeneous()
                 * Translation::new(0.0, 0.0, 3.0).to_homogeneous()
         );
+
+        // The flattened hierarchy lists the root before its children.
+        let order = resources.get::<HierarchyOrder>().unwrap();
+        assert!(!order.is_dirty());
+        assert_eq!(
+            order.entries(),
+            &[
+                HierarchyEntry {
+                    entity: parent,
+                    parent: None
+                },
+                HierarchyEntry {
+                    entity: e1,
+                    parent: Some(0)
+                },
+                HierarchyEntry {
+                    entity: e2,
+                    parent: Some(0)
+                },
+            ]
+        );
+        assert_eq!(order.index_of(e2), Some(2));
+    }
+
+    #[test]
+    fn did_compensate_scale() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut resources = Resources::default();
+        let mut world = Universe::new().create_world();
+
+        let mut hierarchy_maintenance_systems =
+            hierarchy_maintenance_system::build(&mut world, &mut resources);
+        let mut local_transform_system = local_transform_system::build(&mut world, &mut resources);
+        let mut local_to_world_propagate_system =
+            local_to_world_propagate_system::build(&mut world, &mut resources);
+
+        // Root entity, squashed along the Y axis.
+        let parent = *world
+            .insert(
+                (),
+                vec![(
+                    Translation::new(1.0, 0.0, 0.0),
+                    NonUniformScale::new(1.0, 3.0, 1.0),
+                    LocalToWorld::identity(),
+                )],
+            )
+            .first()
+            .unwrap();
+
+        let t = Translation::new(0.0, 1.0, 0.0);
+        let r = Rotation::from_euler_angles(0.0, 0.0, 0.7);
+        let children = world.insert(
+            (),
+            vec![
+                (
+                    t,
+                    r,
+                    ScaleCompensation::SegmentScale,
+                    LocalToParent::identity(),
+                    LocalToWorld::identity(),
+                ),
+                (
+                    t,
+                    r,
+                    ScaleCompensation::AlongAxes,
+                    LocalToParent::identity(),
+                    LocalToWorld::identity(),
+                ),
+            ],
+        );
+        let (e1, e2) = (children[0], children[1]);
+
+        world.add_component(e1, Parent(parent)).unwrap();
+        world.add_component(e2, Parent(parent)).unwrap();
+
+        // Run the needed systems on it.
+        for system in hierarchy_maintenance_systems.iter_mut() {
+            system.run(&mut world, &mut resources);
+            system
+                .command_buffer_mut(world.id())
+                .unwrap()
+                .write(&mut world);
+        }
+        local_transform_system.run(&mut world, &mut resources);
+        local_transform_system
+            .command_buffer_mut(world.id())
+            .unwrap()
+            .write(&mut world);
+        local_to_world_propagate_system.run(&mut world, &mut resources);
+        local_to_world_propagate_system
+            .command_buffer_mut(world.id())
+            .unwrap()
+            .write(&mut world);
+
+        // Segment scale compensation keeps the scaled offset, but the child itself is rigid.
+        let segment = world.get_component::<LocalToWorld>(e1).unwrap().0;
+        assert_eq!(segment.column(3).xyz(), Vector3::new(1.0, 3.0, 0.0));
+        assert!(
+            (segment.fixed_slice::<U3, U3>(0, 0) - r.to_rotation_matrix().into_inner()).norm()
+                < 1e-5
+        );
+
+        // Compensating along the axes leaves the axes orthogonal.
+        let along_axes = world.get_component::<LocalToWorld>(e2).unwrap().0;
+        assert_eq!(along_axes.column(3).xyz(), Vector3::new(1.0, 3.0, 0.0));
+        assert!(
+            along_axes
+                .column(0)
+                .xyz()
+                .dot(&along_axes.column(1).xyz())
+                .abs()
+                < 1e-5
+        );
+    }
+
+    #[test]
+    fn did_propagate_by_depth() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut resources = Resources::default();
+        resources.insert(PropagationMode::ByDepth);
+        let mut world = Universe::new().create_world();
+        let mut systems = transform_system_bundle::build(&mut world, &mut resources);
+
+        let root = *world
+            .insert(
+                (),
+                vec![(Translation::new(1.0, 0.0, 0.0), LocalToWorld::identity())],
+            )
+            .first()
+            .unwrap();
+        let child = *world
+            .insert(
+                (),
+                vec![(
+                    Translation::new(0.0, 2.0, 0.0),
+                    Parent(root),
+                    LocalToParent::identity(),
+                    LocalToWorld::identity(),
+                )],
+            )
+            .first()
+            .unwrap();
+        let grandchild = *world
+            .insert(
+                (),
+                vec![(
+                    Translation::new(0.0, 0.0, 3.0),
+                    Parent(child),
+                    LocalToParent::identity(),
+                    LocalToWorld::identity(),
+                )],
+            )
+            .first()
+            .unwrap();
+
+        let mut run = |world: &mut World, resources: &mut Resources| {
+            for system in systems.iter_mut() {
+                system.run(world, resources);
+                system.command_buffer_mut(world.id()).unwrap().write(world);
+            }
+        };
+        let position = |world: &World, entity| {
+            world
+                .get_component::<LocalToWorld>(entity)
+                .unwrap()
+                .position()
+                .coords
+        };
+
+        // Propagation falls back to the `HierarchyOrder` until the new tags have been applied, so
+        // every frame is correct.
+        run(&mut world, &mut resources);
+
+        assert_eq!(
+            world.get_tag::<HierarchyDepth>(child),
+            Some(&HierarchyDepth(1))
+        );
+        assert_eq!(
+            world.get_tag::<HierarchyDepth>(grandchild),
+            Some(&HierarchyDepth(2))
+        );
+        assert!(world.get_tag::<HierarchyDepth>(root).is_none());
+        assert!((position(&world, grandchild) - Vector3::new(1.0, 2.0, 3.0)).norm() < 1.0e-6);
+
+        // Reparenting moves the grandchild up a depth.
+        *world.get_component_mut::<Parent>(grandchild).unwrap() = Parent(root);
+        run(&mut world, &mut resources);
+
+        assert_eq!(
+            world.get_tag::<HierarchyDepth>(grandchild),
+            Some(&HierarchyDepth(1))
+        );
+        assert!((position(&world, grandchild) - Vector3::new(1.0, 0.0, 3.0)).norm() < 1.0e-6);
+
+        // Once the tags are up to date, each depth is propagated in turn.
+        run(&mut world, &mut resources);
+        assert!(!resources.get::<HierarchyDepthTags>().unwrap().pending);
+        *world.get_component_mut::<Translation>(root).unwrap() = Translation::new(5.0, 0.0, 0.0);
+        run(&mut world, &mut resources);
+
+        assert!((position(&world, child) - Vector3::new(5.0, 2.0, 0.0)).norm() < 1.0e-6);
+        assert!((position(&world, grandchild) - Vector3::new(5.0, 0.0, 3.0)).norm() < 1.0e-6);
     }
 }
@@ -0,0 +1,268 @@
+use crate::{
+    components::*,
+    ecs::{prelude::*, systems::SubWorld},
+    math::Matrix4,
+};
+use std::{any::TypeId, collections::HashMap};
+
+/// A component that contributes to the local transform of an entity, which the
+/// `LocalTransformUpdateSystem` composes into `LocalToParent` and `LocalToWorld`. `Translation`,
+/// `Rotation`, `Scale` and `NonUniformScale` are built in; other crates can add their own (a pivot,
+/// a shear, ...) by implementing this trait and registering the component in the
+/// `LocalTransformComponents` resource.
+pub trait LocalTransformComponent: Component {
+    /// Applies the component to the local matrix composed so far, which starts as the identity and
+    /// goes through every registered component present on the entity in registration order.
+    fn apply(&self, matrix: &mut Matrix4<f32>);
+}
+
+impl LocalTransformComponent for Rotation {
+    #[inline(always)]
+    fn apply(&self, matrix: &mut Matrix4<f32>) {
+        *matrix = self.to_homogeneous() * *matrix;
+    }
+}
+
+impl LocalTransformComponent for Translation {
+    #[inline(always)]
+    fn apply(&self, matrix: &mut Matrix4<f32>) {
+        matrix.append_translation_mut(&self.vector);
+    }
+}
+
+impl LocalTransformComponent for Scale {
+    #[inline(always)]
+    fn apply(&self, matrix: &mut Matrix4<f32>) {
+        matrix.prepend_scaling_mut(self.0);
+    }
+}
+
+impl LocalTransformComponent for NonUniformScale {
+    #[inline(always)]
+    fn apply(&self, matrix: &mut Matrix4<f32>) {
+        matrix.prepend_nonuniform_scaling_mut(&self.0);
+    }
+}
+
+/// The components composed into local transforms, in the order they are applied. This is a
+/// resource read when the transform systems are built, so components must be registered before
+/// then. By default it holds `Rotation`, `Translation`, `Scale` and `NonUniformScale`, which gives
+/// the `(Translation * (Rotation * (Scale | NonUniformScale)))` matrix.
+pub struct LocalTransformComponents {
+    registrations: Vec<fn(bool) -> LocalTransformContribution>,
+    builtin: bool,
+}
+
+impl LocalTransformComponents {
+    /// A registry without any component, not even the built-in ones.
+    pub fn empty() -> Self {
+        Self {
+            registrations: Vec::new(),
+            builtin: false,
+        }
+    }
+
+    /// Appends a component, applied after every component registered so far.
+    pub fn register<T: LocalTransformComponent>(&mut self) -> &mut Self {
+        self.registrations.push(LocalTransformContribution::of::<T>);
+        self.builtin = false;
+        self
+    }
+
+    /// Whether this is the default registry, in which case entities with just a `Translation`, a
+    /// `Rotation` and a `Scale` are composed in batches.
+    #[inline(always)]
+    pub fn is_builtin(&self) -> bool {
+        self.builtin
+    }
+
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.registrations.len()
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.registrations.is_empty()
+    }
+
+    /// Creates the per-system state of every registered component.
+    pub fn contributions(&self) -> Vec<LocalTransformContribution> {
+        self.registrations
+            .iter()
+            .map(|registration| registration(self.builtin))
+            .collect()
+    }
+}
+
+impl Default for LocalTransformComponents {
+    fn default() -> Self {
+        let mut components = Self::empty();
+        components
+            .register::<Rotation>()
+            .register::<Translation>()
+            .register::<Scale>()
+            .register::<NonUniformScale>();
+        components.builtin = true;
+        components
+    }
+}
+
+/// The local transforms being composed by the `LocalTransformUpdateSystem`: a matrix for every
+/// entity of each chunk with a changed component, in chunk order, keyed by the first entity of the
+/// chunk.
+pub type ChunkTransforms = HashMap<Entity, Vec<Matrix4<f32>>>;
+
+/// A registered `LocalTransformComponent`, with the state a system needs to compose it: the
+/// component access to declare, and its own queries. Entities with a `Joint`, with both a `Scale`
+/// and a `NonUniformScale`, that are disabled in the hierarchy or that were baked are left out of
+/// the queries.
+pub struct LocalTransformContribution {
+    component: TypeId,
+    declare: fn(SystemBuilder) -> SystemBuilder,
+    changed: Box<dyn FnMut(&mut SubWorld, &mut ChunkTransforms) + Send + Sync>,
+    apply: Box<dyn FnMut(&mut SubWorld, &mut ChunkTransforms) + Send + Sync>,
+    apply_entity: fn(&SubWorld, Entity, &mut Matrix4<f32>) -> bool,
+}
+
+impl LocalTransformContribution {
+    /// The contribution of `T`. When `batched`, the `Translation + Rotation + Scale` roots are left
+    /// out as well, as the `LocalTransformUpdateSystem` composes them in batches.
+    pub fn of<T: LocalTransformComponent>(batched: bool) -> Self {
+        let changed_query = <Read<T>>::query().filter(
+            changed::<T>()
+                & !component::<Joint>()
+                & !(component::<Scale>() & component::<NonUniformScale>())
+                & !component::<DisabledInHierarchy>()
+                & !component::<StaticTransform>(),
+        );
+        let unbatched_query = <Read<T>>::query().filter(
+            changed::<T>()
+                & !component::<Joint>()
+                & !(component::<Scale>() & component::<NonUniformScale>())
+                & !component::<DisabledInHierarchy>()
+                & !component::<StaticTransform>()
+                & !(component::<Translation>()
+                    & component::<Rotation>()
+                    & component::<Scale>()
+                    & component::<LocalToWorld>()
+                    & !component::<Parent>()
+                    & !component::<LocalToParent>()),
+        );
+        let query = <Read<T>>::query().filter(
+            !component::<Joint>()
+                & !(component::<Scale>() & component::<NonUniformScale>())
+                & !component::<DisabledInHierarchy>()
+                & !component::<StaticTransform>(),
+        );
+        Self {
+            component: TypeId::of::<T>(),
+            declare: |builder: SystemBuilder| builder.read_component::<T>(),
+            changed: Box::new(move |world: &mut SubWorld, chunks: &mut ChunkTransforms| {
+                if batched {
+                    for mut chunk in unbatched_query.iter_chunks(world) {
+                        add_chunk(chunk.iter_entities_mut().map(|(entity, _)| entity), chunks);
+                    }
+                } else {
+                    for mut chunk in changed_query.iter_chunks(world) {
+                        add_chunk(chunk.iter_entities_mut().map(|(entity, _)| entity), chunks);
+                    }
+                }
+            }),
+            apply: Box::new(move |world: &mut SubWorld, chunks: &mut ChunkTransforms| {
+                for mut chunk in query.iter_chunks(world) {
+                    let mut components = chunk.iter_entities_mut().peekable();
+                    let matrices = match components.peek() {
+                        Some((first, _)) => match chunks.get_mut(first) {
+                            Some(matrices) => matrices,
+                            None => continue,
+                        },
+                        None => continue,
+                    };
+                    for ((_, component), matrix) in components.zip(matrices.iter_mut()) {
+                        component.apply(matrix);
+                    }
+                }
+            }),
+            apply_entity: |world: &SubWorld, entity: Entity, matrix: &mut Matrix4<f32>| match world
+                .get_component::<T>(entity)
+            {
+                Some(component) => {
+                    component.apply(matrix);
+                    true
+                }
+                None => false,
+            },
+        }
+    }
+
+    /// Whether this is the contribution of `T`.
+    #[inline(always)]
+    pub fn is<T: LocalTransformComponent>(&self) -> bool {
+        self.component == TypeId::of::<T>()
+    }
+
+    /// Declares read access to the component. This must be done before adding any query.
+    #[inline(always)]
+    pub fn declare_access(&self, builder: SystemBuilder) -> SystemBuilder {
+        (self.declare)(builder)
+    }
+
+    /// Adds every chunk whose component changed since the last call.
+    #[inline(always)]
+    pub fn changed(&mut self, world: &mut SubWorld, chunks: &mut ChunkTransforms) {
+        (self.changed)(world, chunks)
+    }
+
+    /// Applies the component to the matrices of every chunk in `chunks` that has it.
+    #[inline(always)]
+    pub fn apply(&mut self, world: &mut SubWorld, chunks: &mut ChunkTransforms) {
+        (self.apply)(world, chunks)
+    }
+
+    /// Applies the component of a single entity to `matrix`, for systems that recompose the local
+    /// transform of a few entities. Returns whether the entity has the component.
+    #[inline(always)]
+    pub fn apply_entity(
+        &self,
+        world: &SubWorld,
+        entity: Entity,
+        matrix: &mut Matrix4<f32>,
+    ) -> bool {
+        (self.apply_entity)(world, entity, matrix)
+    }
+}
+
+/// Adds the chunk of `entities` to `chunks`, unless it is already there.
+fn add_chunk(mut entities: impl Iterator<Item = Entity>, chunks: &mut ChunkTransforms) {
+    if let Some(first) = entities.next() {
+        let len = 1 + entities.count();
+        chunks
+            .entry(first)
+            .or_insert_with(|| vec![Matrix4::identity(); len]);
+    }
+}
+
+/// Every chunk with at least one changed local transform component, with identity matrices to be
+/// composed by `compose_chunks`.
+pub fn changed_chunks(
+    contributions: &mut [LocalTransformContribution],
+    world: &mut SubWorld,
+) -> ChunkTransforms {
+    let mut chunks = ChunkTransforms::new();
+    for contribution in contributions.iter_mut() {
+        contribution.changed(world, &mut chunks);
+    }
+    chunks
+}
+
+/// Composes the local transform of every entity in `chunks` from its registered components.
+pub fn compose_chunks(
+    contributions: &mut [LocalTransformContribution],
+    world: &mut SubWorld,
+    chunks: &mut ChunkTransforms,
+) {
+    for contribution in contributions.iter_mut() {
+        contribution.apply(world, chunks);
+    }
+}
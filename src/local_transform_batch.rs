@@ -0,0 +1,164 @@
+use crate::{components::*, math::Matrix4};
+
+/// The number of entities `compose_batch` composes at once, in structure of arrays lanes. This is
+/// plain Rust that the compiler is free to auto-vectorize for the target's SIMD width; there are no
+/// explicit SIMD intrinsics.
+pub const LANES: usize = 8;
+
+type Lanes = [f32; LANES];
+
+/// Composes the `(Translation * (Rotation * Scale))` matrix of every entity of a chunk straight
+/// into its `LocalToWorld` slice, `LANES` entities at a time. The entities left over at the end of
+/// the chunk go through `compose_scalar`, which performs the exact same operations, and both match
+/// the matrix composed from the `LocalTransformComponents` bit for bit.
+pub fn compose_batch(
+    translations: &[Translation],
+    rotations: &[Rotation],
+    scales: &[Scale],
+    local_to_worlds: &mut [LocalToWorld],
+) {
+    let len = local_to_worlds.len();
+    assert!(translations.len() == len && rotations.len() == len && scales.len() == len);
+
+    let batched = len - len % LANES;
+    for start in (0..batched).step_by(LANES) {
+        let end = start + LANES;
+        compose_lanes(
+            &translations[start..end],
+            &rotations[start..end],
+            &scales[start..end],
+            &mut local_to_worlds[start..end],
+        );
+    }
+
+    for (((local_to_world, translation), rotation), scale) in local_to_worlds[batched..]
+        .iter_mut()
+        .zip(&translations[batched..])
+        .zip(&rotations[batched..])
+        .zip(&scales[batched..])
+    {
+        *local_to_world = LocalToWorld(compose_scalar(translation, rotation, scale));
+    }
+}
+
+/// The scalar counterpart of `compose_batch`, for a single entity.
+pub fn compose_scalar(
+    translation: &Translation,
+    rotation: &Rotation,
+    scale: &Scale,
+) -> Matrix4<f32> {
+    let q = &rotation.quaternion().coords;
+    let (i, j, k, w) = (q.x, q.y, q.z, q.w);
+    let s = scale.0;
+
+    // Same as `UnitQuaternion::to_rotation_matrix`, so that the result doesn't depend on the path.
+    let ww = w * w;
+    let ii = i * i;
+    let jj = j * j;
+    let kk = k * k;
+    let ij = i * j * 2.0;
+    let wk = w * k * 2.0;
+    let wj = w * j * 2.0;
+    let ik = i * k * 2.0;
+    let jk = j * k * 2.0;
+    let wi = w * i * 2.0;
+
+    let t = &translation.vector;
+    Matrix4::new(
+        (ww + ii - jj - kk) * s,
+        (ij - wk) * s,
+        (wj + ik) * s,
+        t.x,
+        (wk + ij) * s,
+        (ww - ii + jj - kk) * s,
+        (jk - wi) * s,
+        t.y,
+        (ik - wj) * s,
+        (wi + jk) * s,
+        (ww - ii - jj + kk) * s,
+        t.z,
+        0.0,
+        0.0,
+        0.0,
+        1.0,
+    )
+}
+
+fn compose_lanes(
+    translations: &[Translation],
+    rotations: &[Rotation],
+    scales: &[Scale],
+    local_to_worlds: &mut [LocalToWorld],
+) {
+    // Gather every entity into lanes.
+    let mut i = [0.0; LANES];
+    let mut j = [0.0; LANES];
+    let mut k = [0.0; LANES];
+    let mut w = [0.0; LANES];
+    let mut s = [0.0; LANES];
+    for (lane, (rotation, scale)) in rotations.iter().zip(scales).enumerate() {
+        let q = &rotation.quaternion().coords;
+        i[lane] = q.x;
+        j[lane] = q.y;
+        k[lane] = q.z;
+        w[lane] = q.w;
+        s[lane] = scale.0;
+    }
+
+    let two = [2.0; LANES];
+    let ww = mul(&w, &w);
+    let ii = mul(&i, &i);
+    let jj = mul(&j, &j);
+    let kk = mul(&k, &k);
+    let ij = mul(&mul(&i, &j), &two);
+    let wk = mul(&mul(&w, &k), &two);
+    let wj = mul(&mul(&w, &j), &two);
+    let ik = mul(&mul(&i, &k), &two);
+    let jk = mul(&mul(&j, &k), &two);
+    let wi = mul(&mul(&w, &i), &two);
+
+    let m00 = mul(&sub(&sub(&add(&ww, &ii), &jj), &kk), &s);
+    let m01 = mul(&sub(&ij, &wk), &s);
+    let m02 = mul(&add(&wj, &ik), &s);
+    let m10 = mul(&add(&wk, &ij), &s);
+    let m11 = mul(&sub(&add(&sub(&ww, &ii), &jj), &kk), &s);
+    let m12 = mul(&sub(&jk, &wi), &s);
+    let m20 = mul(&sub(&ik, &wj), &s);
+    let m21 = mul(&add(&wi, &jk), &s);
+    let m22 = mul(&add(&sub(&sub(&ww, &ii), &jj), &kk), &s);
+
+    // Scatter the lanes back into the `LocalToWorld` slice.
+    for (lane, (local_to_world, translation)) in
+        local_to_worlds.iter_mut().zip(translations).enumerate()
+    {
+        let t = &translation.vector;
+        *local_to_world = LocalToWorld(Matrix4::new(
+            m00[lane], m01[lane], m02[lane], t.x, m10[lane], m11[lane], m12[lane], t.y, m20[lane],
+            m21[lane], m22[lane], t.z, 0.0, 0.0, 0.0, 1.0,
+        ));
+    }
+}
+
+#[inline(always)]
+fn add(a: &Lanes, b: &Lanes) -> Lanes {
+    zip_with(a, b, |a, b| a + b)
+}
+
+#[inline(always)]
+fn sub(a: &Lanes, b: &Lanes) -> Lanes {
+    zip_with(a, b, |a, b| a - b)
+}
+
+#[inline(always)]
+fn mul(a: &Lanes, b: &Lanes) -> Lanes {
+    zip_with(a, b, |a, b| a * b)
+}
+
+#[inline(always)]
+fn zip_with(a: &Lanes, b: &Lanes, op: impl Fn(f32, f32) -> f32) -> Lanes {
+    let mut result = [0.0; LANES];
+    for ((result, a), b) in result.iter_mut().zip(a).zip(b) {
+        *result = op(*a, *b);
+    }
+    result
+}
@@ -0,0 +1,710 @@
+#![allow(dead_code)]
+use crate::{
+    components::*,
+    ecs::{prelude::*, systems::SubWorld},
+    local_transform::{
+        self, LocalTransformComponent, LocalTransformComponents, LocalTransformContribution,
+    },
+    local_transform_batch,
+    math::Matrix4,
+};
+
+pub fn build(_: &mut World, resources: &mut Resources) -> Box<dyn Schedulable> {
+    resources.insert(SharedLocalTransformSystem);
+    let (mut contributions, batched) = contributions(resources);
+
+    contributions
+        .iter()
+        .fold(
+            SystemBuilder::<()>::new("LocalTransformUpdateSystem"),
+            |builder, contribution| contribution.declare_access(builder),
+        )
+        // Translation + Rotation + Scale roots, composed in batches
+        .with_query(
+            <(
+                Read<Translation>,
+                Read<Rotation>,
+                Read<Scale>,
+                Write<LocalToWorld>,
+            )>::query()
+            .filter(
+                !component::<Parent>()
+                    & !component::<LocalToParent>()
+                    & !component::<NonUniformScale>()
+                    & !component::<Joint>()
+                    & !component::<DisabledInHierarchy>()
+                    & (changed::<Translation>() | changed::<Rotation>() | changed::<Scale>()),
+            ),
+        )
+        // Joint
+        .with_query(<(Read<Joint>, Read<JointValue>)>::query().filter(
+            !component::<DisabledInHierarchy>() & (changed::<Joint>() | changed::<JointValue>()),
+        ))
+        // Joint without a JointValue
+        .with_query(<Read<Joint>>::query().filter(
+            !component::<JointValue>() & !component::<DisabledInHierarchy>() & changed::<Joint>(),
+        ))
+        // Children composed from the registered components, read first to find the chunks to write
+        .with_query(
+            <Read<LocalToParent>>::query()
+                .filter(!component::<Joint>() & !component::<DisabledInHierarchy>()),
+        )
+        .with_query(
+            <Write<LocalToParent>>::query()
+                .filter(!component::<Joint>() & !component::<DisabledInHierarchy>()),
+        )
+        // Roots composed from the registered components, read first to find the chunks to write
+        .with_query(<Read<LocalToWorld>>::query().filter(
+            !component::<Parent>() & !component::<Joint>() & !component::<DisabledInHierarchy>(),
+        ))
+        .with_query(<Write<LocalToWorld>>::query().filter(
+            !component::<Parent>() & !component::<Joint>() & !component::<DisabledInHierarchy>(),
+        ))
+        // Just to issue warnings: Scale + NonUniformScale
+        .with_query(<(Read<Scale>, Read<NonUniformScale>)>::query())
+        .read_component::<Parent>()
+        .read_component::<Joint>()
+        .read_component::<JointValue>()
+        .read_component::<DisabledInHierarchy>()
+        .write_component::<LocalToParent>()
+        .write_component::<LocalToWorld>()
+        .build(move |_commands, world, _, queries| {
+            let (
+                batch,
+                joints,
+                joints_without_value,
+                children_read,
+                children,
+                roots_read,
+                roots,
+                both_scales,
+            ) = queries;
+
+            // Translation + Rotation + Scale roots, a whole chunk at a time. Custom components
+            // could apply to any entity, so this is only done with the default
+            // `LocalTransformComponents`.
+            if batched {
+                let world: &SubWorld = world;
+                // SAFETY: Each chunk is visited by a single thread, which reads its
+                // `Translation`, `Rotation` and `Scale` slices and writes its `LocalToWorld`
+                // slice. No other borrow of these components is alive during the iteration: this
+                // system only runs its other queries once it has finished, and the component
+                // access it declares keeps other systems from running concurrently.
+                unsafe {
+                    batch.par_for_each_chunk_unchecked(world, |mut chunk| {
+                        let translations = chunk.components::<Translation>().unwrap();
+                        let rotations = chunk.components::<Rotation>().unwrap();
+                        let scales = chunk.components::<Scale>().unwrap();
+                        let mut local_to_worlds = chunk.components_mut::<LocalToWorld>().unwrap();
+                        local_transform_batch::compose_batch(
+                            &translations,
+                            &rotations,
+                            &scales,
+                            &mut local_to_worlds,
+                        );
+                    });
+                }
+            }
+
+            // Every other chunk with a changed local transform component, composed once and
+            // written to its `LocalToParent`, or to its `LocalToWorld` if it is not a child. Only
+            // those chunks are borrowed mutably, so that the others don't show up as changed.
+            let mut chunks = local_transform::changed_chunks(&mut contributions, world);
+            if !chunks.is_empty() {
+                local_transform::compose_chunks(&mut contributions, world, &mut chunks);
+
+                let composed = children_read
+                    .iter_chunks(world)
+                    .map(|mut chunk| composed_chunk(chunk.iter_entities_mut(), &chunks))
+                    .collect::<Vec<_>>();
+                for (mut chunk, first) in children.iter_chunks_mut(world).zip(composed) {
+                    if let Some(matrices) = first.and_then(|first| chunks.get(&first)) {
+                        for ((_, mut local_to_parent), matrix) in
+                            chunk.iter_entities_mut().zip(matrices)
+                        {
+                            *local_to_parent = LocalToParent(*matrix);
+                        }
+                    }
+                }
+
+                let composed = roots_read
+                    .iter_chunks(world)
+                    .map(|mut chunk| composed_chunk(chunk.iter_entities_mut(), &chunks))
+                    .collect::<Vec<_>>();
+                for (mut chunk, first) in roots.iter_chunks_mut(world).zip(composed) {
+                    if let Some(matrices) = first.and_then(|first| chunks.get(&first)) {
+                        for ((_, mut local_to_world), matrix) in
+                            chunk.iter_entities_mut().zip(matrices)
+                        {
+                            *local_to_world = LocalToWorld(*matrix);
+                        }
+                    }
+                }
+            }
+
+            // Joints, which take precedence over the transform components
+            let joint_matrices = joints
+                .iter_entities(world)
+                .map(|(entity, (joint, value))| (entity, joint.local_to_parent(value.0)))
+                .chain(
+                    joints_without_value
+                        .iter_entities(world)
+                        .map(|(entity, joint)| (entity, joint.local_to_parent(0.0))),
+                )
+                .collect::<Vec<_>>();
+            for (entity, matrix) in joint_matrices {
+                write_local(world, entity, matrix);
+            }
+
+            // Just to issue warnings: Scale + NonUniformScale
+            both_scales
+                .iter_entities(world)
+                .for_each(|(entity, (_scale, _non_uniform_scale))| {
+                    log::warn!(
+                        "Entity {:?} has both a Scale and NonUniformScale component.",
+                        entity
+                    );
+                });
+        })
+}
+
+/// The first entity of a chunk, if the chunk is one of `chunks`.
+fn composed_chunk<T>(
+    mut entities: impl Iterator<Item = (Entity, T)>,
+    chunks: &local_transform::ChunkTransforms,
+) -> Option<Entity> {
+    entities
+        .next()
+        .map(|(first, _)| first)
+        .filter(|first| chunks.contains_key(first))
+}
+
+/// Writes the local transform of `entity` to its `LocalToParent`, or to its `LocalToWorld` if it is
+/// not a child.
+fn write_local(world: &mut SubWorld, entity: Entity, matrix: Matrix4<f32>) {
+    if let Some(mut local_to_parent) = world.get_component_mut::<LocalToParent>(entity) {
+        *local_to_parent = LocalToParent(matrix);
+    }
+    if world.get_component::<Parent>(entity).is_none() {
+        if let Some(mut local_to_world) = world.get_component_mut::<LocalToWorld>(entity) {
+            *local_to_world = LocalToWorld(matrix);
+        }
+    }
+}
+
+/// Marks that a `LocalTransformUpdateSystem` has been built.
+struct SharedLocalTransformSystem;
+
+/// Builds the `LocalTransformUpdateSystem` for the deprecated `local_to_parent_system` and
+/// `local_to_world_system`, which used to be added together: if one was already built (by either
+/// of them, `build` or the `transform_system_bundle`), this one does nothing.
+pub(crate) fn build_shared(world: &mut World, resources: &mut Resources) -> Box<dyn Schedulable> {
+    if resources.contains::<SharedLocalTransformSystem>() {
+        log::debug!("A LocalTransformUpdateSystem was already built, building an empty one");
+        return SystemBuilder::<()>::new("LocalTransformUpdateSystem")
+            .build(move |_commands, _world, _resource, _queries| {});
+    }
+    build(world, resources)
+}
+
+/// The components registered in the `LocalTransformComponents` resource (inserting the default
+/// ones if there is none yet), as contributions for a system to compose, and whether they are the
+/// built-in ones.
+pub fn contributions(resources: &mut Resources) -> (Vec<LocalTransformContribution>, bool) {
+    if !resources.contains::<LocalTransformComponents>() {
+        resources.insert(LocalTransformComponents::default());
+    }
+    let components = resources.get::<LocalTransformComponents>().unwrap();
+    (components.contributions(), components.is_builtin())
+}
+
+/// Declares the component access `compose_entity` and `compose_entity_with` need, for the systems
+/// that call them. This must be done before adding any query.
+pub fn declare_compose_access(
+    contributions: &[LocalTransformContribution],
+    builder: SystemBuilder,
+) -> SystemBuilder {
+    contributions.iter().fold(
+        builder
+            .read_component::<Joint>()
+            .read_component::<JointValue>(),
+        |builder, contribution| contribution.declare_access(builder),
+    )
+}
+
+/// The local transform of `entity`, for systems that recompose a few entities outside of the
+/// `LocalTransformUpdateSystem`: that of its `Joint` if it has one, otherwise its registered
+/// components applied in order. Returns `None` if it has neither.
+pub fn compose_entity(
+    contributions: &[LocalTransformContribution],
+    world: &SubWorld,
+    entity: Entity,
+) -> Option<Matrix4<f32>> {
+    compose_entity_inner(contributions, world, entity, |_, _| false)
+}
+
+/// Like `compose_entity`, but applies `component` in place of the entity's own `T`, which it may
+/// not have yet (eg. when it is being added through a command buffer).
+pub fn compose_entity_with<T: LocalTransformComponent>(
+    contributions: &[LocalTransformContribution],
+    world: &SubWorld,
+    entity: Entity,
+    component: &T,
+) -> Matrix4<f32> {
+    compose_entity_inner(contributions, world, entity, |contribution, matrix| {
+        if contribution.is::<T>() {
+            component.apply(matrix);
+            true
+        } else {
+            false
+        }
+    })
+    .unwrap_or_else(Matrix4::identity)
+}
+
+fn compose_entity_inner<F>(
+    contributions: &[LocalTransformContribution],
+    world: &SubWorld,
+    entity: Entity,
+    mut apply_override: F,
+) -> Option<Matrix4<f32>>
+where
+    F: FnMut(&LocalTransformContribution, &mut Matrix4<f32>) -> bool,
+{
+    if let Some(joint) = world.get_component::<Joint>(entity) {
+        let value = world
+            .get_component::<JointValue>(entity)
+            .map_or(0.0, |value| value.0);
+        return Some(joint.local_to_parent(value));
+    }
+
+    let mut matrix = Matrix4::identity();
+    let mut composed = false;
+    for contribution in contributions {
+        composed |= apply_override(contribution, &mut matrix)
+            || contribution.apply_entity(world, entity, &mut matrix);
+    }
+    if composed {
+        Some(matrix)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        math::{Isometry3, Point3, UnitQuaternion, Vector3},
+        transform_system_bundle,
+    };
+
+    #[test]
+    fn correct_parent_transformation() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut resources = Resources::default();
+        let mut world = Universe::new().create_world();
+        let mut system = build(&mut world, &mut resources);
+
+        let ltw = LocalToParent::identity();
+        let t = Translation::new(1.0, 2.0, 3.0);
+        let r = Rotation::from_euler_angles(1.0, 2.0, 3.0);
+        let s = Scale(2.0);
+        let nus = NonUniformScale::new(1.0, 2.0, 3.0);
+
+        // Add every combination of transform types.
+        let translation = *world.insert((), vec![(ltw, t)]).first().unwrap();
+        let rotation = *world.insert((), vec![(ltw, r)]).first().unwrap();
+        let scale = *world.insert((), vec![(ltw, s)]).first().unwrap();
+        let non_uniform_scale = *world.insert((), vec![(ltw, nus)]).first().unwrap();
+        let translation_and_rotation = *world.insert((), vec![(ltw, t, r)]).first().unwrap();
+        let translation_and_scale = *world.insert((), vec![(ltw, t, s)]).first().unwrap();
+        let translation_and_nus = *world.insert((), vec![(ltw, t, nus)]).first().unwrap();
+        let rotation_scale = *world.insert((), vec![(ltw, r, s)]).first().unwrap();
+        let rotation_nus = *world.insert((), vec![(ltw, r, nus)]).first().unwrap();
+        let translation_rotation_scale = *world.insert((), vec![(ltw, t, r, s)]).first().unwrap();
+        let translation_rotation_nus = *world.insert((), vec![(ltw, t, r, nus)]).first().unwrap();
+
+        // Run the system
+        system.run(&mut world, &mut resources);
+        system
+            .command_buffer_mut(world.id())
+            .unwrap()
+            .write(&mut world);
+
+        // Verify that each was transformed correctly.
+        assert_eq!(
+            world.get_component::<LocalToParent>(translation).unwrap().0,
+            t.to_homogeneous()
+        );
+        assert_eq!(
+            world.get_component::<LocalToParent>(rotation).unwrap().0,
+            r.to_homogeneous()
+        );
+        assert_eq!(
+            world.get_component::<LocalToParent>(scale).unwrap().0,
+            Matrix4::new_scaling(s.0),
+        );
+        assert_eq!(
+            world
+                .get_component::<LocalToParent>(non_uniform_scale)
+                .unwrap()
+                .0,
+            Matrix4::new_nonuniform_scaling(&nus.0),
+        );
+        assert_eq!(
+            world
+                .get_component::<LocalToParent>(translation_and_rotation)
+                .unwrap()
+                .0,
+            r.to_homogeneous().append_translation(&t.vector),
+        );
+        assert_eq!(
+            world
+                .get_component::<LocalToParent>(translation_and_scale)
+                .unwrap()
+                .0,
+            t.to_homogeneous().prepend_scaling(s.0),
+        );
+        assert_eq!(
+            world
+                .get_component::<LocalToParent>(translation_and_nus)
+                .unwrap()
+                .0,
+            t.to_homogeneous().prepend_nonuniform_scaling(&nus.0),
+        );
+        assert_eq!(
+            world
+                .get_component::<LocalToParent>(rotation_scale)
+                .unwrap()
+                .0,
+            r.to_homogeneous().prepend_scaling(s.0)
+        );
+        assert_eq!(
+            world
+                .get_component::<LocalToParent>(rotation_nus)
+                .unwrap()
+                .0,
+            r.to_homogeneous().prepend_nonuniform_scaling(&nus.0)
+        );
+        assert_eq!(
+            world
+                .get_component::<LocalToParent>(translation_rotation_scale)
+                .unwrap()
+                .0,
+            r.to_homogeneous()
+                .append_translation(&t.vector)
+                .prepend_scaling(s.0)
+        );
+        assert_eq!(
+            world
+                .get_component::<LocalToParent>(translation_rotation_nus)
+                .unwrap()
+                .0,
+            r.to_homogeneous()
+                .append_translation(&t.vector)
+                .prepend_nonuniform_scaling(&nus.0)
+        );
+    }
+
+    #[test]
+    fn correct_joint_transformation() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut resources = Resources::default();
+        let mut world = Universe::new().create_world();
+        let mut system = build(&mut world, &mut resources);
+
+        let ltp = LocalToParent::identity();
+        let origin = Isometry3::translation(0.0, 0.0, 1.0);
+        let revolute = Joint::revolute(origin, Vector3::z_axis(), -1.0, 1.0);
+        let prismatic = Joint::prismatic(origin, Vector3::x_axis(), 0.0, 2.0);
+        let fixed = Joint::fixed(origin);
+
+        // Joints take precedence over transform components.
+        let t = Translation::new(1.0, 2.0, 3.0);
+        let revolute_entity = *world
+            .insert((), vec![(ltp, t, revolute, JointValue(2.0))])
+            .first()
+            .unwrap();
+        let prismatic_entity = *world
+            .insert((), vec![(ltp, prismatic, JointValue(0.5))])
+            .first()
+            .unwrap();
+        let fixed_entity = *world.insert((), vec![(ltp, fixed)]).first().unwrap();
+        // A joint at the root of a hierarchy drives its `LocalToWorld` instead.
+        let root_entity = *world
+            .insert((), vec![(LocalToWorld::identity(), fixed)])
+            .first()
+            .unwrap();
+
+        system.run(&mut world, &mut resources);
+        system
+            .command_buffer_mut(world.id())
+            .unwrap()
+            .write(&mut world);
+
+        // The revolute joint value is clamped to its upper limit.
+        assert_eq!(
+            world
+                .get_component::<LocalToParent>(revolute_entity)
+                .unwrap()
+                .0,
+            (origin * UnitQuaternion::from_axis_angle(&Vector3::z_axis(), 1.0)).to_homogeneous()
+        );
+        assert_eq!(
+            world
+                .get_component::<LocalToParent>(prismatic_entity)
+                .unwrap()
+                .0,
+            Matrix4::new_translation(&Vector3::new(0.5, 0.0, 1.0))
+        );
+        assert_eq!(
+            world
+                .get_component::<LocalToParent>(fixed_entity)
+                .unwrap()
+                .0,
+            origin.to_homogeneous()
+        );
+        assert_eq!(
+            world.get_component::<LocalToWorld>(root_entity).unwrap().0,
+            origin.to_homogeneous()
+        );
+    }
+
+    #[test]
+    fn correct_world_transformation() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut resources = Resources::default();
+        let mut world = Universe::new().create_world();
+        let mut system = build(&mut world, &mut resources);
+
+        let ltw = LocalToWorld::identity();
+        let t = Translation::new(1.0, 2.0, 3.0);
+        let r = Rotation::from_euler_angles(1.0, 2.0, 3.0);
+        let s = Scale(2.0);
+        let nus = NonUniformScale::new(1.0, 2.0, 3.0);
+
+        // Add every combination of transform types.
+        let translation = *world.insert((), vec![(ltw, t)]).first().unwrap();
+        let rotation = *world.insert((), vec![(ltw, r)]).first().unwrap();
+        let scale = *world.insert((), vec![(ltw, s)]).first().unwrap();
+        let non_uniform_scale = *world.insert((), vec![(ltw, nus)]).first().unwrap();
+        let translation_and_rotation = *world.insert((), vec![(ltw, t, r)]).first().unwrap();
+        let translation_and_scale = *world.insert((), vec![(ltw, t, s)]).first().unwrap();
+        let translation_and_nus = *world.insert((), vec![(ltw, t, nus)]).first().unwrap();
+        let rotation_scale = *world.insert((), vec![(ltw, r, s)]).first().unwrap();
+        let rotation_nus = *world.insert((), vec![(ltw, r, nus)]).first().unwrap();
+        let translation_rotation_scale = *world.insert((), vec![(ltw, t, r, s)]).first().unwrap();
+        let translation_rotation_nus = *world.insert((), vec![(ltw, t, r, nus)]).first().unwrap();
+
+        // Run the system
+        system.run(&mut world, &mut resources);
+        system
+            .command_buffer_mut(world.id())
+            .unwrap()
+            .write(&mut world);
+
+        // Verify that each was transformed correctly.
+        assert_eq!(
+            world.get_component::<LocalToWorld>(translation).unwrap().0,
+            t.to_homogeneous()
+        );
+        assert_eq!(
+            world.get_component::<LocalToWorld>(rotation).unwrap().0,
+            r.to_homogeneous()
+        );
+        assert_eq!(
+            world.get_component::<LocalToWorld>(scale).unwrap().0,
+            Matrix4::new_scaling(s.0),
+        );
+        assert_eq!(
+            world
+                .get_component::<LocalToWorld>(non_uniform_scale)
+                .unwrap()
+                .0,
+            Matrix4::new_nonuniform_scaling(&nus.0),
+        );
+        assert_eq!(
+            world
+                .get_component::<LocalToWorld>(translation_and_rotation)
+                .unwrap()
+                .0,
+            r.to_homogeneous().append_translation(&t.vector),
+        );
+        assert_eq!(
+            world
+                .get_component::<LocalToWorld>(translation_and_scale)
+                .unwrap()
+                .0,
+            t.to_homogeneous().prepend_scaling(s.0),
+        );
+        assert_eq!(
+            world
+                .get_component::<LocalToWorld>(translation_and_nus)
+                .unwrap()
+                .0,
+            t.to_homogeneous().prepend_nonuniform_scaling(&nus.0),
+        );
+        assert_eq!(
+            world
+                .get_component::<LocalToWorld>(rotation_scale)
+                .unwrap()
+                .0,
+            r.to_homogeneous().prepend_scaling(s.0)
+        );
+        assert_eq!(
+            world.get_component::<LocalToWorld>(rotation_nus).unwrap().0,
+            r.to_homogeneous().prepend_nonuniform_scaling(&nus.0)
+        );
+        assert_eq!(
+            world
+                .get_component::<LocalToWorld>(translation_rotation_scale)
+                .unwrap()
+                .0,
+            r.to_homogeneous()
+                .append_translation(&t.vector)
+                .prepend_scaling(s.0)
+        );
+        assert_eq!(
+            world
+                .get_component::<LocalToWorld>(translation_rotation_nus)
+                .unwrap()
+                .0,
+            r.to_homogeneous()
+                .append_translation(&t.vector)
+                .prepend_nonuniform_scaling(&nus.0)
+        );
+    }
+
+    #[test]
+    fn custom_local_transform_component() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        /// Rotates and scales around a point other than the origin.
+        #[derive(Debug, PartialEq, Clone, Copy)]
+        struct Pivot(Vector3<f32>);
+
+        impl LocalTransformComponent for Pivot {
+            fn apply(&self, matrix: &mut Matrix4<f32>) {
+                *matrix = *matrix * Matrix4::new_translation(&-self.0);
+                matrix.append_translation_mut(&self.0);
+            }
+        }
+
+        let mut resources = Resources::default();
+        let mut components = LocalTransformComponents::default();
+        components.register::<Pivot>();
+        resources.insert(components);
+        let mut world = Universe::new().create_world();
+        let mut system = build(&mut world, &mut resources);
+
+        let entity = *world
+            .insert(
+                (),
+                vec![(
+                    LocalToWorld::identity(),
+                    Scale(2.0),
+                    Pivot(Vector3::new(1.0, 0.0, 0.0)),
+                )],
+            )
+            .first()
+            .unwrap();
+
+        system.run(&mut world, &mut resources);
+        system
+            .command_buffer_mut(world.id())
+            .unwrap()
+            .write(&mut world);
+
+        // Scaling around (1, 0, 0) leaves it in place.
+        let local_to_world = world.get_component::<LocalToWorld>(entity).unwrap();
+        assert!((local_to_world.position().coords - Vector3::new(-1.0, 0.0, 0.0)).norm() < 1.0e-6);
+        assert!(
+            (local_to_world.transform_point(&Point3::new(1.0, 0.0, 0.0))
+                - Point3::new(1.0, 0.0, 0.0))
+            .norm()
+                < 1.0e-6
+        );
+        drop(local_to_world);
+
+        // Systems recomposing a local transform after propagation apply it too.
+        let mut world = Universe::new().create_world();
+        let mut systems = transform_system_bundle::build(&mut world, &mut resources);
+        let entity = *world
+            .insert(
+                (),
+                vec![(
+                    LocalToWorld::identity(),
+                    Rotation::identity(),
+                    Pivot(Vector3::new(1.0, 0.0, 0.0)),
+                    LookAt::new(Point3::new(0.0, 0.0, -10.0)),
+                )],
+            )
+            .first()
+            .unwrap();
+
+        for system in systems.iter_mut() {
+            system.run(&mut world, &mut resources);
+            system
+                .command_buffer_mut(world.id())
+                .unwrap()
+                .write(&mut world);
+        }
+
+        // Turned around to face -Z, rotating around (1, 0, 0).
+        let local_to_world = world.get_component::<LocalToWorld>(entity).unwrap();
+        assert!((local_to_world.0.column(2).xyz() - Vector3::new(0.0, 0.0, -1.0)).norm() < 1.0e-5);
+        assert!(
+            (local_to_world.transform_point(&Point3::new(1.0, 0.0, 0.0))
+                - Point3::new(1.0, 0.0, 0.0))
+            .norm()
+                < 1.0e-5
+        );
+    }
+
+    #[test]
+    fn batched_world_transformation() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut resources = Resources::default();
+        let mut world = Universe::new().create_world();
+        let mut system = build(&mut world, &mut resources);
+
+        // A few full batches, and a remainder for the scalar fallback.
+        let count = local_transform_batch::LANES * 2 + 3;
+        let components = (0..count)
+            .map(|index| {
+                let index = index as f32;
+                (
+                    LocalToWorld::identity(),
+                    Translation::new(index, -2.0 * index, 0.5),
+                    Rotation::from_euler_angles(0.1 * index, 1.0 - 0.2 * index, 0.3),
+                    Scale(1.0 + 0.25 * index),
+                )
+            })
+            .collect::<Vec<_>>();
+        let entities = world.insert((), components.clone()).to_vec();
+
+        system.run(&mut world, &mut resources);
+        system
+            .command_buffer_mut(world.id())
+            .unwrap()
+            .write(&mut world);
+
+        // Both paths give the exact same matrix as composing the components one by one.
+        for (entity, (_, t, r, s)) in entities.iter().zip(components.iter()) {
+            let mut expected = Matrix4::identity();
+            r.apply(&mut expected);
+            t.apply(&mut expected);
+            s.apply(&mut expected);
+            assert_eq!(local_transform_batch::compose_scalar(t, r, s), expected);
+            assert_eq!(
+                world.get_component::<LocalToWorld>(*entity).unwrap().0,
+                expected
+            );
+        }
+    }
+}
@@ -0,0 +1,332 @@
+#![allow(dead_code)]
+use crate::{
+    animation_sampling_system::AnimationTime,
+    components::*,
+    ecs::{prelude::*, systems::SubWorld},
+    local_to_world_propagate_system,
+    local_transform::LocalTransformContribution,
+    local_transform_system,
+    math::{Point3, UnitQuaternion, Vector3},
+};
+
+pub fn build(_: &mut World, resources: &mut Resources) -> Box<dyn Schedulable> {
+    if !resources.contains::<AnimationTime>() {
+        resources.insert(AnimationTime::default());
+    }
+
+    let (contributions, _) = local_transform_system::contributions(resources);
+    let builder = SystemBuilder::<()>::new("LookAtSystem");
+    let builder = local_transform_system::declare_compose_access(&contributions, builder);
+    local_to_world_propagate_system::declare_repropagate_access(builder)
+        .read_resource::<AnimationTime>()
+        // Entities looking at something
+        .with_query(
+            <Read<LookAt>>::query()
+                .filter(component::<LocalToWorld>() & !component::<StaticTransform>()),
+        )
+        .write_component::<Rotation>()
+        .write_component::<LocalToParent>()
+        .build(move |commands, world, time, query| {
+            let look_ats = query
+                .iter_entities(world)
+                .map(|(entity, look_at)| (entity, *look_at))
+                .collect::<Vec<_>>();
+
+            for (entity, look_at) in look_ats {
+                look_at_target(
+                    world,
+                    commands,
+                    &contributions,
+                    entity,
+                    &look_at,
+                    time.delta_seconds,
+                );
+            }
+        })
+}
+
+/// The world space position of a target, or `None` if the target entity has no `LocalToWorld`.
+pub fn target_position(world: &SubWorld, target: &TransformTarget) -> Option<Point3<f32>> {
+    match target {
+        TransformTarget::Entity(entity) => world
+            .get_component::<LocalToWorld>(*entity)
+            .map(|local_to_world| local_to_world.position()),
+        TransformTarget::Point(point) => Some(*point),
+    }
+}
+
+fn look_at_target(
+    world: &mut SubWorld,
+    commands: &mut CommandBuffer,
+    contributions: &[LocalTransformContribution],
+    entity: Entity,
+    look_at: &LookAt,
+    delta_seconds: f32,
+) {
+    let local_to_world = match world.get_component::<LocalToWorld>(entity) {
+        Some(local_to_world) => *local_to_world,
+        None => return,
+    };
+    let target = match target_position(world, &look_at.target) {
+        Some(target) => target,
+        None => {
+            log::warn!(
+                "Entity {} is looking at a target that does not have a LocalToWorld",
+                entity
+            );
+            return;
+        }
+    };
+
+    let direction = match (target - local_to_world.position()).try_normalize(f32::EPSILON) {
+        Some(direction) => direction,
+        None => {
+            log::trace!("Entity {} is on top of its target", entity);
+            return;
+        }
+    };
+    let up = up_axis(&direction, &look_at.up);
+
+    // The rotation is written in the parent's space.
+    let parent_local_to_world = world
+        .get_component::<Parent>(entity)
+        .and_then(|parent| world.get_component::<LocalToWorld>(parent.0).map(|e| *e));
+    let parent_rotation = parent_local_to_world
+        .map(|parent_local_to_world| parent_local_to_world.rotation())
+        .unwrap_or_else(UnitQuaternion::identity);
+    let desired = parent_rotation.inverse() * UnitQuaternion::face_towards(&direction, &up);
+
+    let current = world.get_component::<Rotation>(entity).map(|e| *e);
+    let rotation = match (current, look_at.max_angular_speed) {
+        (Some(current), Some(max_angular_speed)) => {
+            let max_angle = max_angular_speed * delta_seconds;
+            let angle = current.angle_to(&desired);
+            if angle > max_angle {
+                current
+                    .try_slerp(&desired, max_angle / angle, 1.0e-6)
+                    .unwrap_or(desired)
+            } else {
+                desired
+            }
+        }
+        _ => desired,
+    };
+    let rotation = Rotation(rotation);
+
+    if current.is_some() {
+        if let Some(mut current) = world.get_component_mut::<Rotation>(entity) {
+            *current = rotation;
+        }
+    } else {
+        commands.add_component(entity, rotation);
+    }
+
+    // Recompute the local transform, then the world transform of the whole subtree.
+    let local =
+        local_transform_system::compose_entity_with(contributions, world, entity, &rotation);
+
+    let new_local_to_world = match parent_local_to_world {
+        Some(parent_local_to_world) => {
+            let local_to_parent = LocalToParent(local);
+            if let Some(mut current) = world.get_component_mut::<LocalToParent>(entity) {
+                *current = local_to_parent;
+            }
+            local_to_world_propagate_system::child_local_to_world(
+                world,
+                entity,
+                &parent_local_to_world,
+                &local_to_parent,
+            )
+        }
+        None => LocalToWorld(local),
+    };
+
+    local_to_world_propagate_system::repropagate(world, entity, new_local_to_world);
+}
+
+/// `up`, or the first of the Z and X axes that isn't parallel to `direction` if it is, so that an
+/// entity looking straight up or down still has a well defined rotation.
+fn up_axis(direction: &Vector3<f32>, up: &Vector3<f32>) -> Vector3<f32> {
+    [*up, Vector3::z(), Vector3::x()]
+        .iter()
+        .copied()
+        .find(|axis| axis.cross(direction).norm_squared() > f32::EPSILON)
+        .unwrap_or(*up)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::transform_system_bundle;
+
+    /// A child looking at a point under a rotated parent (so that its `Rotation` must be written in
+    /// the parent's space), with a grandchild following it. Returns the child, grandchild and target.
+    fn insert_hierarchy(world: &mut World) -> (Entity, Entity, Point3<f32>) {
+        let parent = *world
+            .insert(
+                (),
+                vec![(
+                    Translation::new(1.0, 0.0, 0.0),
+                    Rotation::from_euler_angles(0.0, 1.0, 0.0),
+                    LocalToWorld::identity(),
+                )],
+            )
+            .first()
+            .unwrap();
+        let target = Point3::new(4.0, 5.0, 6.0);
+        let child = *world
+            .insert(
+                (),
+                vec![(
+                    Translation::new(0.0, 2.0, 0.0),
+                    Rotation::identity(),
+                    LookAt::new(target),
+                    Parent(parent),
+                    LocalToParent::identity(),
+                    LocalToWorld::identity(),
+                )],
+            )
+            .first()
+            .unwrap();
+        let grandchild = *world
+            .insert(
+                (),
+                vec![(
+                    Translation::new(0.0, 0.0, 1.0),
+                    Parent(child),
+                    LocalToParent::identity(),
+                    LocalToWorld::identity(),
+                )],
+            )
+            .first()
+            .unwrap();
+        (child, grandchild, target)
+    }
+
+    fn assert_looking_at(world: &World, child: Entity, grandchild: Entity, target: Point3<f32>) {
+        let local_to_world = *world.get_component::<LocalToWorld>(child).unwrap();
+        let forward = local_to_world.0.column(2).xyz();
+        let expected: Vector3<f32> = (target - local_to_world.position()).normalize();
+        assert!((forward - expected).norm() < 1e-4);
+
+        // The grandchild sits one unit in front of the child, towards the target.
+        let grandchild_local_to_world = *world.get_component::<LocalToWorld>(grandchild).unwrap();
+        assert!(
+            (grandchild_local_to_world.position() - (local_to_world.position() + expected)).norm()
+                < 1e-4
+        );
+    }
+
+    #[test]
+    fn did_look_at() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut resources = Resources::default();
+        let mut world = Universe::new().create_world();
+        let mut systems = transform_system_bundle::build(&mut world, &mut resources);
+        let (child, grandchild, target) = insert_hierarchy(&mut world);
+
+        // Run twice, the first run only establishes the hierarchy.
+        for _ in 0..2 {
+            for system in systems.iter_mut() {
+                system.run(&mut world, &mut resources);
+                system
+                    .command_buffer_mut(world.id())
+                    .unwrap()
+                    .write(&mut world);
+            }
+        }
+
+        assert_looking_at(&world, child, grandchild, target);
+    }
+
+    #[test]
+    fn did_look_at_through_schedule() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        // Command buffers are only flushed at the end of the schedule, so anything the systems
+        // after propagation read must have been written directly.
+        let mut resources = Resources::default();
+        let mut world = Universe::new().create_world();
+        let mut schedule = transform_system_bundle::build(&mut world, &mut resources)
+            .into_iter()
+            .fold(Schedule::builder(), |builder, system| {
+                builder.add_system(system)
+            })
+            .build();
+        let (child, grandchild, target) = insert_hierarchy(&mut world);
+
+        for _ in 0..3 {
+            schedule.execute(&mut world, &mut resources);
+        }
+
+        assert_looking_at(&world, child, grandchild, target);
+    }
+
+    fn run_systems(
+        systems: &mut [Box<dyn Schedulable>],
+        world: &mut World,
+        resources: &mut Resources,
+    ) {
+        for system in systems.iter_mut() {
+            system.run(world, resources);
+            system.command_buffer_mut(world.id()).unwrap().write(world);
+        }
+    }
+
+    #[test]
+    fn looks_along_up() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut resources = Resources::default();
+        let mut world = Universe::new().create_world();
+        let mut systems = transform_system_bundle::build(&mut world, &mut resources);
+        let entity = *world
+            .insert(
+                (),
+                vec![(
+                    Translation::identity(),
+                    Rotation::identity(),
+                    LookAt::new(Point3::new(0.0, 5.0, 0.0)),
+                    LocalToWorld::identity(),
+                )],
+            )
+            .first()
+            .unwrap();
+
+        run_systems(&mut systems, &mut world, &mut resources);
+
+        let local_to_world = *world.get_component::<LocalToWorld>(entity).unwrap();
+        let forward = local_to_world.0.column(2).xyz();
+        assert!((forward - Vector3::y()).norm() < 1e-4);
+    }
+
+    #[test]
+    fn turns_at_max_angular_speed() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut resources = Resources::default();
+        let mut world = Universe::new().create_world();
+        let mut systems = transform_system_bundle::build(&mut world, &mut resources);
+        resources.get_mut::<AnimationTime>().unwrap().delta_seconds = 0.5;
+        let entity = *world
+            .insert(
+                (),
+                vec![(
+                    Translation::identity(),
+                    Rotation::identity(),
+                    LookAt::new(Point3::new(1.0, 0.0, 0.0)).with_max_angular_speed(0.5),
+                    LocalToWorld::identity(),
+                )],
+            )
+            .first()
+            .unwrap();
+
+        // The target is a quarter turn away, so each update turns 0.25 radians towards it.
+        for expected in [0.25, 0.5].iter() {
+            run_systems(&mut systems, &mut world, &mut resources);
+            let rotation = *world.get_component::<Rotation>(entity).unwrap();
+            assert!((rotation.angle() - expected).abs() < 1e-4);
+        }
+    }
+}
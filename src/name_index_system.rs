@@ -0,0 +1,125 @@
+#![allow(dead_code)]
+use crate::{components::*, ecs::prelude::*, hierarchy_path};
+
+pub fn build(_: &mut World, _: &mut Resources) -> Box<dyn Schedulable> {
+    SystemBuilder::<()>::new("NameIndexSystem")
+        // Indexed roots
+        .with_query(<Read<NameIndex>>::query())
+        // Changed children, updated by the `HierarchyMaintenanceSystem`
+        .with_query(<Read<Children>>::query().filter(changed::<Children>()))
+        // Changed names
+        .with_query(<Read<Name>>::query().filter(changed::<Name>()))
+        .read_component::<Parent>()
+        .read_component::<Children>()
+        .read_component::<Name>()
+        .write_component::<NameIndex>()
+        .build(move |_commands, world, _resource, queries| {
+            let (indices, changed_children, changed_names) = queries;
+            let changed = changed_children.iter(world).next().is_some()
+                || changed_names.iter(world).next().is_some();
+
+            let rebuilt = indices
+                .iter_entities(world)
+                .filter(|(_, index)| changed || !index.is_built())
+                .map(|(entity, _)| (entity, hierarchy_path::descendant_paths(world, entity)))
+                .collect::<Vec<_>>();
+
+            for (entity, paths) in rebuilt {
+                log::trace!(
+                    "Rebuilt the name index of {} ({} paths)",
+                    entity,
+                    paths.len()
+                );
+                if let Some(mut index) = world.get_component_mut::<NameIndex>(entity) {
+                    index.set_paths(paths);
+                }
+            }
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{hierarchy_maintenance_system, hierarchy_path::*};
+
+    #[test]
+    fn did_index_names() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut resources = Resources::default();
+        let mut world = Universe::new().create_world();
+        let mut systems = hierarchy_maintenance_system::build(&mut world, &mut resources);
+        systems.push(build(&mut world, &mut resources));
+
+        let tank = *world
+            .insert((), vec![(NameIndex::new(), LocalToWorld::identity())])
+            .first()
+            .unwrap();
+        let turret = *world
+            .insert(
+                (),
+                vec![(
+                    Name::new("turret"),
+                    Parent(tank),
+                    LocalToParent::identity(),
+                    LocalToWorld::identity(),
+                )],
+            )
+            .first()
+            .unwrap();
+        let barrel = *world
+            .insert(
+                (),
+                vec![(
+                    Name::new("barrel"),
+                    Parent(turret),
+                    LocalToParent::identity(),
+                    LocalToWorld::identity(),
+                )],
+            )
+            .first()
+            .unwrap();
+
+        let mut run = |world: &mut World, resources: &mut Resources| {
+            for system in systems.iter_mut() {
+                system.run(world, resources);
+                system.command_buffer_mut(world.id()).unwrap().write(world);
+            }
+        };
+        run(&mut world, &mut resources);
+        run(&mut world, &mut resources);
+
+        assert_eq!(find_by_path(&world, tank, "turret/barrel"), Some(barrel));
+        assert_eq!(find_by_path(&world, tank, ""), Some(tank));
+        assert_eq!(find_by_path(&world, tank, "barrel"), None);
+        assert_eq!(
+            path_of(&world, tank, barrel).as_deref(),
+            Some("turret/barrel")
+        );
+        assert_eq!(path_of(&world, turret, tank), None);
+        {
+            let index = world.get_component::<NameIndex>(tank).unwrap();
+            assert_eq!(index.len(), 2);
+            assert_eq!(index.get("turret/barrel"), Some(barrel));
+        }
+
+        // Adding a child updates the index.
+        let muzzle = *world
+            .insert(
+                (),
+                vec![(
+                    Name::new("muzzle"),
+                    Parent(barrel),
+                    LocalToParent::identity(),
+                    LocalToWorld::identity(),
+                )],
+            )
+            .first()
+            .unwrap();
+        run(&mut world, &mut resources);
+        run(&mut world, &mut resources);
+
+        let index = world.get_component::<NameIndex>(tank).unwrap();
+        assert_eq!(index.get("turret/barrel/muzzle"), Some(muzzle));
+    }
+}
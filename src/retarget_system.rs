@@ -0,0 +1,228 @@
+#![allow(dead_code)]
+use crate::{
+    components::*,
+    ecs::{prelude::*, systems::SubWorld},
+    math::{Translation3, UnitQuaternion, Vector3},
+};
+use std::collections::HashMap;
+
+/// A named bone of a skeleton, along with its rest pose accumulated from the skeleton's root.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct RestBone {
+    pub entity: Entity,
+    pub rest: RestPose,
+    /// Rest rotation of the bone's parent, relative to the skeleton's root.
+    pub parent_rotation: UnitQuaternion<f32>,
+    /// Rest rotation of the bone, relative to the skeleton's root.
+    pub rotation: UnitQuaternion<f32>,
+    /// Rest position of the bone, relative to the skeleton's root.
+    pub position: Vector3<f32>,
+}
+
+pub fn build(_: &mut World, _: &mut Resources) -> Box<dyn Schedulable> {
+    SystemBuilder::<()>::new("RetargetSystem")
+        // Retargeted skeleton roots
+        .with_query(<Read<Retarget>>::query())
+        .read_component::<Children>()
+        .read_component::<Name>()
+        .read_component::<RestPose>()
+        .write_component::<Translation>()
+        .write_component::<Rotation>()
+        .build(move |commands, world, _resource, query| {
+            let retargets = query
+                .iter_entities(world)
+                .map(|(entity, retarget)| (entity, (*retarget).clone()))
+                .collect::<Vec<_>>();
+
+            for (root, retarget) in retargets {
+                retarget_skeleton(world, commands, root, &retarget);
+            }
+        })
+}
+
+/// Collects the named bones under `root` by walking `Children`. Bones without a `RestPose` are
+/// treated as having an identity rest pose.
+pub fn rest_bones(world: &SubWorld, root: Entity) -> HashMap<String, RestBone> {
+    let mut bones = HashMap::new();
+    let mut stack = vec![(root, UnitQuaternion::identity(), Vector3::zeros())];
+    while let Some((entity, parent_rotation, parent_position)) = stack.pop() {
+        let children = match world.get_component::<Children>(entity) {
+            Some(children) => children.0.clone(),
+            None => continue,
+        };
+
+        for child in children {
+            let rest = world
+                .get_component::<RestPose>(child)
+                .map(|e| *e)
+                .unwrap_or_default();
+            let bone = RestBone {
+                entity: child,
+                rest,
+                parent_rotation,
+                rotation: parent_rotation * rest.rotation,
+                position: parent_position + parent_rotation * rest.translation,
+            };
+            if let Some(name) = world.get_component::<Name>(child) {
+                bones.insert(name.0.clone(), bone);
+            }
+            stack.push((child, bone.rotation, bone.position));
+        }
+    }
+    bones
+}
+
+/// Converts a source bone's local rotation to the matching target bone, such that both bones turn
+/// by the same amount (relative to the root) away from their rest pose.
+pub fn retarget_rotation(
+    source: &RestBone,
+    target: &RestBone,
+    rotation: &UnitQuaternion<f32>,
+) -> UnitQuaternion<f32> {
+    target.parent_rotation.inverse()
+        * source.parent_rotation
+        * rotation
+        * source.rotation.inverse()
+        * target.rotation
+}
+
+fn retarget_skeleton(
+    world: &mut SubWorld,
+    commands: &mut CommandBuffer,
+    root: Entity,
+    retarget: &Retarget,
+) {
+    let source_bones = rest_bones(world, retarget.source);
+    let target_bones = rest_bones(world, root);
+
+    for (name, target) in target_bones.iter() {
+        let source = match source_bones.get(name) {
+            Some(source) => source,
+            None => continue,
+        };
+
+        let source_rotation = world.get_component::<Rotation>(source.entity).map(|e| *e);
+        if let Some(source_rotation) = source_rotation {
+            let rotation = Rotation(retarget_rotation(source, target, &source_rotation));
+            match world.get_component_mut::<Rotation>(target.entity) {
+                Some(mut current) => *current = rotation,
+                None => commands.add_component(target.entity, rotation),
+            }
+        }
+
+        if *name != retarget.hips {
+            continue;
+        }
+
+        let source_translation = world
+            .get_component::<Translation>(source.entity)
+            .map(|e| *e);
+        if let Some(source_translation) = source_translation {
+            let scale = retarget.translation_scale.unwrap_or_else(|| {
+                let source_length = source.position.norm();
+                if source_length > f32::EPSILON {
+                    target.position.norm() / source_length
+                } else {
+                    1.0
+                }
+            });
+            let parent_rotation = target.parent_rotation.inverse() * source.parent_rotation;
+            let translation = Translation(Translation3::from(
+                parent_rotation * source_translation.vector * scale,
+            ));
+            match world.get_component_mut::<Translation>(target.entity) {
+                Some(mut current) => *current = translation,
+                None => commands.add_component(target.entity, translation),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::hierarchy_maintenance_system;
+    use std::f32::consts::PI;
+
+    #[test]
+    fn did_retarget() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut resources = Resources::default();
+        let mut world = Universe::new().create_world();
+        let mut systems = hierarchy_maintenance_system::build(&mut world, &mut resources);
+        systems.push(build(&mut world, &mut resources));
+
+        let spine_rotation = UnitQuaternion::from_axis_angle(&Vector3::x_axis(), PI / 6.0);
+        let target_spine_rest = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), PI / 2.0);
+
+        // root -> hips -> spine, with the target twice as tall and its spine rotated at rest.
+        let mut skeleton = |hips_height: f32, spine_rest: UnitQuaternion<f32>| {
+            let root = *world
+                .insert((), vec![(LocalToWorld::identity(),)])
+                .first()
+                .unwrap();
+            let hips = *world
+                .insert(
+                    (),
+                    vec![(
+                        Name::from("hips"),
+                        RestPose::new(
+                            Vector3::new(0.0, hips_height, 0.0),
+                            UnitQuaternion::identity(),
+                        ),
+                        Translation::new(0.0, hips_height, 0.0),
+                        Rotation::identity(),
+                        Parent(root),
+                        LocalToParent::identity(),
+                        LocalToWorld::identity(),
+                    )],
+                )
+                .first()
+                .unwrap();
+            let spine = *world
+                .insert(
+                    (),
+                    vec![(
+                        Name::from("spine"),
+                        RestPose::new(Vector3::new(0.0, 0.5, 0.0), spine_rest),
+                        Rotation(spine_rest),
+                        Parent(hips),
+                        LocalToParent::identity(),
+                        LocalToWorld::identity(),
+                    )],
+                )
+                .first()
+                .unwrap();
+            (root, hips, spine)
+        };
+        let (source_root, source_hips, source_spine) = skeleton(1.0, UnitQuaternion::identity());
+        let (target_root, target_hips, target_spine) = skeleton(2.0, target_spine_rest);
+
+        // Animate the source.
+        *world.get_component_mut::<Translation>(source_hips).unwrap() =
+            Translation::new(1.0, 1.0, 0.0);
+        *world.get_component_mut::<Rotation>(source_spine).unwrap() = Rotation(spine_rotation);
+        world
+            .add_component(target_root, Retarget::new(source_root, "hips"))
+            .unwrap();
+
+        // The first run builds the hierarchy, the second retargets.
+        for _ in 0..2 {
+            for system in systems.iter_mut() {
+                system.run(&mut world, &mut resources);
+                system
+                    .command_buffer_mut(world.id())
+                    .unwrap()
+                    .write(&mut world);
+            }
+        }
+
+        assert_eq!(
+            *world.get_component::<Translation>(target_hips).unwrap(),
+            Translation::new(2.0, 2.0, 0.0)
+        );
+        let rotation = world.get_component::<Rotation>(target_spine).unwrap();
+        assert!(rotation.angle_to(&(spine_rotation * target_spine_rest)) < 1.0e-5);
+    }
+}
@@ -0,0 +1,295 @@
+#![allow(dead_code)]
+use crate::{
+    animation_clip::{AnimationClip, ChannelValue},
+    animation_sampling_system,
+    components::*,
+    ecs::{prelude::*, systems::SubWorld},
+    math::{Translation3, Unit, UnitQuaternion, Vector3},
+};
+use std::collections::HashMap;
+
+pub fn build(_: &mut World, _: &mut Resources) -> Box<dyn Schedulable> {
+    SystemBuilder::<()>::new("RootMotionSystem")
+        // Entities moved by root motion
+        .with_query(<Write<RootMotion>>::query())
+        .read_component::<Parent>()
+        .read_component::<Children>()
+        .read_component::<Name>()
+        .read_component::<NameIndex>()
+        .read_component::<AnimationPlayer>()
+        .write_component::<Translation>()
+        .write_component::<Rotation>()
+        .build(move |commands, world, _resource, query| {
+            let mut root_motions = query
+                .iter_entities(world)
+                .map(|(entity, root_motion)| (entity, (*root_motion).clone()))
+                .collect::<HashMap<_, _>>();
+
+            for (root, root_motion) in root_motions.iter_mut() {
+                extract(world, commands, *root, root_motion);
+            }
+
+            for (entity, mut root_motion) in query.iter_entities_mut(world) {
+                if let Some(updated) = root_motions.remove(&entity) {
+                    if *root_motion != updated {
+                        *root_motion = updated;
+                    }
+                }
+            }
+        })
+}
+
+/// The rotation about `up` of `rotation`, such that `rotation = heading * rest` where `rest` has no
+/// rotation about `up`.
+pub fn heading(rotation: &UnitQuaternion<f32>, up: &Unit<Vector3<f32>>) -> UnitQuaternion<f32> {
+    let (_, twist) = swing_twist(&rotation.inverse(), up);
+    twist.inverse()
+}
+
+fn extract(
+    world: &mut SubWorld,
+    commands: &mut CommandBuffer,
+    root: Entity,
+    root_motion: &mut RootMotion,
+) {
+    let bone = match animation_sampling_system::resolve_path(world, root, &root_motion.bone) {
+        Some(bone) => bone,
+        None => {
+            log::trace!(
+                "Root motion bone {:?} not found under {}",
+                root_motion.bone,
+                root
+            );
+            return;
+        }
+    };
+
+    let player = world
+        .get_component::<AnimationPlayer>(root)
+        .map(|player| (*player).clone());
+    let current = RootMotionSample {
+        translation: world
+            .get_component::<Translation>(bone)
+            .map(|translation| translation.vector)
+            .unwrap_or_else(Vector3::zeros),
+        rotation: world
+            .get_component::<Rotation>(bone)
+            .map(|rotation| rotation.0)
+            .unwrap_or_else(UnitQuaternion::identity),
+        time: player.as_ref().map(|player| player.time),
+    };
+
+    // The bone still holds what was written last time, it wasn't animated since.
+    if let Some(written) = root_motion.written {
+        if written.translation == current.translation && written.rotation == current.rotation {
+            return;
+        }
+    }
+
+    let reference = *root_motion.reference.get_or_insert(current);
+    let segments = match (root_motion.previous, player) {
+        (Some(previous), Some(player)) if wrapped(&player, &previous, &current) => {
+            // The clip looped: move to the end of the clip, then from its start.
+            let (end, start) = if player.speed >= 0.0 {
+                (player.clip.duration, 0.0)
+            } else {
+                (0.0, player.clip.duration)
+            };
+            let path = &root_motion.bone;
+            vec![
+                (previous, sample_bone(&player.clip, path, end, &current)),
+                (sample_bone(&player.clip, path, start, &current), current),
+            ]
+        }
+        (Some(previous), _) => vec![(previous, current)],
+        (None, _) => vec![],
+    };
+
+    let heading_of = |rotation: &UnitQuaternion<f32>| {
+        if root_motion.extract_rotation {
+            heading(rotation, &root_motion.up)
+        } else {
+            UnitQuaternion::identity()
+        }
+    };
+    let reference_heading = heading_of(&reference.rotation);
+
+    // Motion in the space of the root, relative to its transform before this run.
+    let mut delta_translation = Vector3::zeros();
+    let mut delta_rotation = UnitQuaternion::identity();
+    for (from, to) in segments {
+        let from_heading = heading_of(&from.rotation);
+        let to_heading = heading_of(&to.rotation);
+        let offset = (from_heading * reference_heading.inverse()).inverse()
+            * (to.translation - from.translation).component_mul(&root_motion.translation_axes);
+        delta_translation += delta_rotation * offset;
+        delta_rotation *= to_heading * from_heading.inverse();
+    }
+
+    // Hold the bone at its reference.
+    let bone_translation = current.translation
+        - (current.translation - reference.translation)
+            .component_mul(&root_motion.translation_axes);
+    let bone_rotation =
+        reference_heading * heading_of(&current.rotation).inverse() * current.rotation;
+    write(world, commands, bone, bone_translation, bone_rotation);
+
+    let root_translation = world
+        .get_component::<Translation>(root)
+        .map(|translation| translation.vector)
+        .unwrap_or_else(Vector3::zeros);
+    let root_rotation = world
+        .get_component::<Rotation>(root)
+        .map(|rotation| rotation.0)
+        .unwrap_or_else(UnitQuaternion::identity);
+    write(
+        world,
+        commands,
+        root,
+        root_translation + root_rotation * delta_translation,
+        root_rotation * delta_rotation,
+    );
+
+    root_motion.previous = Some(current);
+    root_motion.written = Some(RootMotionSample {
+        translation: bone_translation,
+        rotation: bone_rotation,
+        time: None,
+    });
+}
+
+/// Whether a looping player wrapped around between two samples.
+fn wrapped(
+    player: &AnimationPlayer,
+    previous: &RootMotionSample,
+    current: &RootMotionSample,
+) -> bool {
+    match (player.looping, previous.time, current.time) {
+        (true, Some(previous), Some(current)) if player.speed >= 0.0 => current < previous,
+        (true, Some(previous), Some(current)) => current > previous,
+        _ => false,
+    }
+}
+
+/// Samples the bone's channels of `clip` at `time`, keeping the values of `default` for properties
+/// the clip does not animate.
+fn sample_bone(
+    clip: &AnimationClip,
+    path: &str,
+    time: f32,
+    default: &RootMotionSample,
+) -> RootMotionSample {
+    let mut sample = RootMotionSample {
+        time: Some(time),
+        ..*default
+    };
+    for channel in clip.channels.iter().filter(|channel| channel.path == path) {
+        match channel.track.sample(time) {
+            Some(ChannelValue::Translation(translation)) => sample.translation = translation,
+            Some(ChannelValue::Rotation(rotation)) => sample.rotation = rotation,
+            _ => {}
+        }
+    }
+    sample
+}
+
+fn write(
+    world: &mut SubWorld,
+    commands: &mut CommandBuffer,
+    entity: Entity,
+    translation: Vector3<f32>,
+    rotation: UnitQuaternion<f32>,
+) {
+    let translation = Translation(Translation3::from(translation));
+    match world.get_component_mut::<Translation>(entity) {
+        Some(mut current) => *current = translation,
+        None => commands.add_component(entity, translation),
+    }
+
+    let rotation = Rotation(rotation);
+    match world.get_component_mut::<Rotation>(entity) {
+        Some(mut current) => *current = rotation,
+        None => commands.add_component(entity, rotation),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        animation_clip::{Channel, ChannelTrack, Interpolation, Keyframe, Track},
+        animation_sampling_system::{self, AnimationTime},
+        hierarchy_maintenance_system,
+    };
+    use std::sync::Arc;
+
+    #[test]
+    fn did_extract_root_motion() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut resources = Resources::default();
+        let mut world = Universe::new().create_world();
+        let mut systems = hierarchy_maintenance_system::build(&mut world, &mut resources);
+        systems.push(animation_sampling_system::build(&mut world, &mut resources));
+        systems.push(build(&mut world, &mut resources));
+
+        // The hips walk 2 units forward in a second.
+        let clip = AnimationClip::new(vec![Channel::new(
+            "hips",
+            ChannelTrack::Translation(Track::new(
+                Interpolation::Linear,
+                vec![
+                    Keyframe::new(0.0, Vector3::new(0.0, 1.0, 0.0)),
+                    Keyframe::new(1.0, Vector3::new(0.0, 1.0, 2.0)),
+                ],
+            )),
+        )]);
+
+        let root = *world
+            .insert(
+                (),
+                vec![(
+                    AnimationPlayer::new(Arc::new(clip)),
+                    RootMotion::new("hips"),
+                    Translation::identity(),
+                    Rotation::identity(),
+                    LocalToWorld::identity(),
+                )],
+            )
+            .first()
+            .unwrap();
+        let hips = *world
+            .insert(
+                (),
+                vec![(
+                    Name::from("hips"),
+                    Translation::identity(),
+                    Parent(root),
+                    LocalToParent::identity(),
+                    LocalToWorld::identity(),
+                )],
+            )
+            .first()
+            .unwrap();
+
+        for delta_seconds in [0.0, 0.5, 0.5].iter() {
+            resources.get_mut::<AnimationTime>().unwrap().delta_seconds = *delta_seconds;
+            for system in systems.iter_mut() {
+                system.run(&mut world, &mut resources);
+                system
+                    .command_buffer_mut(world.id())
+                    .unwrap()
+                    .write(&mut world);
+            }
+        }
+
+        assert_eq!(
+            *world.get_component::<Translation>(root).unwrap(),
+            Translation::new(0.0, 0.0, 2.0)
+        );
+        assert_eq!(
+            *world.get_component::<Translation>(hips).unwrap(),
+            Translation::new(0.0, 1.0, 0.0)
+        );
+    }
+}
@@ -0,0 +1,192 @@
+#![allow(dead_code)]
+use crate::{
+    components::*,
+    ecs::{prelude::*, systems::SubWorld},
+    local_to_world_propagate_system,
+    local_transform::LocalTransformContribution,
+    local_transform_system,
+    math::Matrix4,
+};
+
+pub fn build(_: &mut World, resources: &mut Resources) -> Box<dyn Schedulable> {
+    let (contributions, _) = local_transform_system::contributions(resources);
+    let builder = SystemBuilder::<()>::new("SkeletonSystem");
+    let builder = local_transform_system::declare_compose_access(&contributions, builder);
+    local_to_world_propagate_system::declare_repropagate_access(builder)
+        // Changed skeletons
+        .with_query(
+            <(Read<Skeleton>, Read<LocalToWorld>)>::query()
+                .filter(changed::<Skeleton>() | changed::<LocalToWorld>()),
+        )
+        // Bone attachments
+        .with_query(
+            <Read<BoneAttachment>>::query()
+                .filter(component::<LocalToWorld>() & !component::<StaticTransform>()),
+        )
+        .write_component::<SkeletonPose>()
+        .build(move |commands, world, _resource, queries| {
+            let (skeletons, attachments) = queries;
+
+            let poses = skeletons
+                .iter_entities(world)
+                .map(|(entity, (skeleton, local_to_world))| {
+                    let mut pose = Vec::with_capacity(skeleton.len());
+                    skeleton.compute_pose(&local_to_world, &mut pose);
+                    (entity, SkeletonPose(pose))
+                })
+                .collect::<Vec<_>>();
+
+            for (entity, pose) in poses {
+                match world.get_component_mut::<SkeletonPose>(entity) {
+                    Some(mut current) => *current = pose,
+                    None => commands.add_component(entity, pose),
+                }
+            }
+
+            let attachments = attachments
+                .iter_entities(world)
+                .map(|(entity, attachment)| (entity, *attachment))
+                .collect::<Vec<_>>();
+
+            for (entity, attachment) in attachments {
+                attach(world, &contributions, entity, &attachment);
+            }
+        })
+}
+
+fn attach(
+    world: &mut SubWorld,
+    contributions: &[LocalTransformContribution],
+    entity: Entity,
+    attachment: &BoneAttachment,
+) {
+    let bone_to_world = match world
+        .get_component::<SkeletonPose>(attachment.skeleton)
+        .and_then(|pose| pose.get(attachment.bone).copied())
+    {
+        Some(bone_to_world) => LocalToWorld(bone_to_world),
+        None => {
+            log::trace!(
+                "Bone {} of {} has no pose (yet), can't attach {}",
+                attachment.bone,
+                attachment.skeleton,
+                entity
+            );
+            return;
+        }
+    };
+
+    let local = LocalToParent(
+        local_transform_system::compose_entity(contributions, world, entity)
+            .unwrap_or_else(Matrix4::identity),
+    );
+    let local_to_world = local_to_world_propagate_system::child_local_to_world(
+        world,
+        entity,
+        &bone_to_world,
+        &local,
+    );
+    local_to_world_propagate_system::repropagate(world, entity, local_to_world);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        math::{Matrix4, UnitQuaternion, Vector3},
+        transform_system_bundle,
+    };
+    use std::f32::consts::PI;
+
+    #[test]
+    fn did_pose_skeleton() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut resources = Resources::default();
+        let mut world = Universe::new().create_world();
+        let mut systems = transform_system_bundle::build(&mut world, &mut resources);
+
+        // An arm pointing up, bent 90° at the elbow.
+        let mut skeleton = Skeleton::with_capacity(3);
+        let shoulder = skeleton.add_bone(
+            "shoulder",
+            None,
+            Vector3::new(0.0, 1.0, 0.0),
+            UnitQuaternion::identity(),
+        );
+        let elbow = skeleton.add_bone(
+            "elbow",
+            Some(shoulder),
+            Vector3::new(0.0, 1.0, 0.0),
+            UnitQuaternion::from_axis_angle(&Vector3::z_axis(), -PI / 2.0),
+        );
+        let hand = skeleton.add_bone(
+            "hand",
+            Some(elbow),
+            Vector3::new(0.0, 1.0, 0.0),
+            UnitQuaternion::identity(),
+        );
+
+        let character = *world
+            .insert(
+                (),
+                vec![(
+                    skeleton,
+                    Translation::new(5.0, 0.0, 0.0),
+                    LocalToWorld::identity(),
+                )],
+            )
+            .first()
+            .unwrap();
+
+        // A sword held in the hand, with a child of its own.
+        let sword = *world
+            .insert(
+                (),
+                vec![(
+                    BoneAttachment::new(character, hand),
+                    Translation::new(0.0, 0.5, 0.0),
+                    LocalToWorld::identity(),
+                )],
+            )
+            .first()
+            .unwrap();
+        let tip = *world
+            .insert(
+                (),
+                vec![(
+                    Translation::new(0.0, 1.0, 0.0),
+                    Parent(sword),
+                    LocalToParent::identity(),
+                    LocalToWorld::identity(),
+                )],
+            )
+            .first()
+            .unwrap();
+
+        for _ in 0..2 {
+            for system in systems.iter_mut() {
+                system.run(&mut world, &mut resources);
+                system
+                    .command_buffer_mut(world.id())
+                    .unwrap()
+                    .write(&mut world);
+            }
+        }
+
+        let pose = world.get_component::<SkeletonPose>(character).unwrap();
+        let expected = Matrix4::new_translation(&Vector3::new(5.0, 2.0, 0.0))
+            * UnitQuaternion::from_axis_angle(&Vector3::z_axis(), -PI / 2.0).to_homogeneous();
+        assert!((pose[elbow] - expected).norm() < 1.0e-5);
+        drop(pose);
+
+        // The hand is at (6, 2, 0), pointing along +X.
+        let sword_position = world
+            .get_component::<LocalToWorld>(sword)
+            .unwrap()
+            .position();
+        assert!((sword_position.coords - Vector3::new(6.5, 2.0, 0.0)).norm() < 1.0e-5);
+        let tip_position = world.get_component::<LocalToWorld>(tip).unwrap().position();
+        assert!((tip_position.coords - Vector3::new(7.5, 2.0, 0.0)).norm() < 1.0e-5);
+    }
+}
@@ -0,0 +1,276 @@
+#![allow(dead_code)]
+use crate::{
+    components::*,
+    dual_quaternion::DualQuaternion,
+    ecs::{prelude::*, systems::SubWorld},
+    math::{Matrix4, U3},
+};
+use std::collections::{HashMap, HashSet};
+
+pub fn build(_: &mut World, _: &mut Resources) -> Box<dyn Schedulable> {
+    // The mesh and joint transforms each palette was last computed from, so that only skins whose
+    // joints moved are recomputed without looking at every changed `LocalToWorld` in the world.
+    let mut sources = HashMap::<Entity, Vec<Matrix4<f32>>>::new();
+
+    SystemBuilder::<()>::new("SkinningSystem")
+        // Skinned meshes
+        .with_query(<(Read<Skin>, Read<LocalToWorld>)>::query())
+        // Changed skins
+        .with_query(<Read<Skin>>::query().filter(changed::<Skin>()))
+        .read_component::<LocalToWorld>()
+        .write_component::<JointPalette>()
+        .write_component::<DualQuaternionPalette>()
+        .build(move |commands, world, _resource, queries| {
+            let (skins, changed_skins) = queries;
+            let changed = changed_skins
+                .iter_entities(world)
+                .map(|(entity, _)| entity)
+                .collect::<HashSet<_>>();
+
+            let mut palettes = Vec::new();
+            let mut seen = HashSet::new();
+            for (entity, (skin, local_to_world)) in skins.iter_entities(world) {
+                seen.insert(entity);
+                if skin.joints.len() != skin.inverse_bind_matrices.len() {
+                    log::warn!(
+                        "Skin of {} has {} joints but {} inverse bind matrices",
+                        entity,
+                        skin.joints.len(),
+                        skin.inverse_bind_matrices.len()
+                    );
+                    continue;
+                }
+
+                let mut transforms = Vec::with_capacity(skin.joints.len() + 1);
+                transforms.push(local_to_world.0);
+                transforms.extend(joint_transforms(world, &skin));
+                if !changed.contains(&entity) && sources.get(&entity) == Some(&transforms) {
+                    continue;
+                }
+
+                let palette = joint_palette(&skin, &local_to_world, &transforms[1..]);
+                sources.insert(entity, transforms);
+                palettes.push((entity, skin.output, palette));
+            }
+            sources.retain(|entity, _| seen.contains(entity));
+
+            // Switching `Skin::output` removes the palette of the other kind.
+            for (entity, output, palette) in palettes {
+                match output {
+                    SkinningOutput::Matrices => {
+                        if world
+                            .get_component::<DualQuaternionPalette>(entity)
+                            .is_some()
+                        {
+                            commands.remove_component::<DualQuaternionPalette>(entity);
+                        }
+                        match world.get_component_mut::<JointPalette>(entity) {
+                            Some(mut current) => *current = palette,
+                            None => commands.add_component(entity, palette),
+                        }
+                    }
+                    SkinningOutput::DualQuaternions => {
+                        if world.get_component::<JointPalette>(entity).is_some() {
+                            commands.remove_component::<JointPalette>(entity);
+                        }
+                        let palette = dual_quaternion_palette(&palette);
+                        match world.get_component_mut::<DualQuaternionPalette>(entity) {
+                            Some(mut current) => *current = palette,
+                            None => commands.add_component(entity, palette),
+                        }
+                    }
+                }
+            }
+        })
+}
+
+/// The `LocalToWorld` of every joint of `skin`, or the identity for joints without one.
+pub fn joint_transforms<'a>(
+    world: &'a SubWorld,
+    skin: &'a Skin,
+) -> impl Iterator<Item = Matrix4<f32>> + 'a {
+    skin.joints.iter().map(
+        move |joint| match world.get_component::<LocalToWorld>(*joint) {
+            Some(joint_to_world) => joint_to_world.0,
+            None => {
+                log::warn!("Skin joint {} does not have a LocalToWorld", joint);
+                Matrix4::identity()
+            }
+        },
+    )
+}
+
+/// Computes `joint_to_world[i] * inverse_bind(i)` for every joint of `skin`, relative to the mesh's
+/// `LocalToWorld`.
+///
+/// Panics if `skin` doesn't have one inverse bind matrix per joint, or `joint_to_world` doesn't
+/// have one transform per joint.
+pub fn joint_palette(
+    skin: &Skin,
+    local_to_world: &LocalToWorld,
+    joint_to_world: &[Matrix4<f32>],
+) -> JointPalette {
+    assert_eq!(skin.joints.len(), skin.inverse_bind_matrices.len());
+    assert_eq!(skin.joints.len(), joint_to_world.len());
+
+    let world_to_mesh = local_to_world
+        .try_inverse()
+        .unwrap_or_else(Matrix4::identity);
+    JointPalette(
+        joint_to_world
+            .iter()
+            .zip(skin.inverse_bind_matrices.iter())
+            .map(|(joint_to_world, inverse_bind)| world_to_mesh * joint_to_world * inverse_bind)
+            .collect(),
+    )
+}
+
+/// Splits every skinning matrix into a rigid transform and the scale and shear applied before it.
+pub fn dual_quaternion_palette(palette: &JointPalette) -> DualQuaternionPalette {
+    let (dual_quaternions, scales) = palette
+        .iter()
+        .map(|matrix| {
+            let linear = matrix.fixed_slice::<U3, U3>(0, 0).into_owned();
+            let rotation = rotation_of_basis(&linear);
+            let scale = rotation.to_rotation_matrix().transpose() * linear;
+            (
+                DualQuaternion::from_rotation_translation(&rotation, &matrix.column(3).xyz()),
+                scale,
+            )
+        })
+        .unzip();
+    DualQuaternionPalette {
+        dual_quaternions,
+        scales,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::math::{Matrix3, Point3, UnitQuaternion, Vector3};
+    use std::f32::consts::PI;
+
+    #[test]
+    fn correct_palette() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut resources = Resources::default();
+        let mut world = Universe::new().create_world();
+        let mut system = build(&mut world, &mut resources);
+
+        let joint = *world
+            .insert(
+                (),
+                vec![(LocalToWorld(Matrix4::new_translation(&Vector3::new(
+                    1.0, 2.0, 0.0,
+                ))),)],
+            )
+            .first()
+            .unwrap();
+        let mesh = *world
+            .insert(
+                (),
+                vec![(
+                    Skin::new(
+                        vec![joint],
+                        vec![Matrix4::new_translation(&Vector3::new(0.0, -1.0, 0.0))],
+                    ),
+                    LocalToWorld(Matrix4::new_translation(&Vector3::new(1.0, 0.0, 0.0))),
+                )],
+            )
+            .first()
+            .unwrap();
+
+        system.run(&mut world, &mut resources);
+        system
+            .command_buffer_mut(world.id())
+            .unwrap()
+            .write(&mut world);
+
+        let palette = world.get_component::<JointPalette>(mesh).unwrap();
+        assert_eq!(palette.len(), 1);
+        assert!(
+            (palette[0] - Matrix4::new_translation(&Vector3::new(0.0, 1.0, 0.0))).norm() < 1.0e-6
+        );
+        drop(palette);
+
+        // Moving the joint updates the palette.
+        *world.get_component_mut::<LocalToWorld>(joint).unwrap() =
+            LocalToWorld(Matrix4::new_translation(&Vector3::new(1.0, 3.0, 0.0)));
+        system.run(&mut world, &mut resources);
+        system
+            .command_buffer_mut(world.id())
+            .unwrap()
+            .write(&mut world);
+
+        let palette = world.get_component::<JointPalette>(mesh).unwrap();
+        assert!(
+            (palette[0] - Matrix4::new_translation(&Vector3::new(0.0, 2.0, 0.0))).norm() < 1.0e-6
+        );
+    }
+
+    #[test]
+    fn correct_dual_quaternion_palette() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut resources = Resources::default();
+        let mut world = Universe::new().create_world();
+        let mut system = build(&mut world, &mut resources);
+
+        let rotation = UnitQuaternion::from_axis_angle(&Vector3::z_axis(), PI / 2.0);
+        let joint_to_world = Matrix4::new_translation(&Vector3::new(1.0, 2.0, 3.0))
+            * rotation.to_homogeneous()
+            * Matrix4::new_nonuniform_scaling(&Vector3::new(2.0, 1.0, 1.0));
+        let joint = *world
+            .insert((), vec![(LocalToWorld(joint_to_world),)])
+            .first()
+            .unwrap();
+        let mesh = *world
+            .insert(
+                (),
+                vec![(
+                    Skin::new(vec![joint], vec![Matrix4::identity()])
+                        .with_output(SkinningOutput::DualQuaternions),
+                    LocalToWorld::identity(),
+                )],
+            )
+            .first()
+            .unwrap();
+
+        system.run(&mut world, &mut resources);
+        system
+            .command_buffer_mut(world.id())
+            .unwrap()
+            .write(&mut world);
+
+        let palette = world.get_component::<DualQuaternionPalette>(mesh).unwrap();
+        assert!((palette.dual_quaternions[0].rotation().angle_to(&rotation)) < 1.0e-6);
+        assert!(
+            (palette.dual_quaternions[0].translation() - Vector3::new(1.0, 2.0, 3.0)).norm()
+                < 1.0e-6
+        );
+        assert!(
+            (palette.scales[0] - Matrix3::from_diagonal(&Vector3::new(2.0, 1.0, 1.0))).norm()
+                < 1.0e-6
+        );
+
+        // Both parts together give back the skinning matrix.
+        let point = Point3::new(1.0, 1.0, 1.0);
+        let skinned = palette.dual_quaternions[0].transform_point(&(palette.scales[0] * point));
+        assert!((skinned - joint_to_world.transform_point(&point)).norm() < 1.0e-5);
+        assert!(world.get_component::<JointPalette>(mesh).is_none());
+        drop(palette);
+
+        // Switching the output replaces the palette.
+        world.get_component_mut::<Skin>(mesh).unwrap().output = SkinningOutput::Matrices;
+        system.run(&mut world, &mut resources);
+        system
+            .command_buffer_mut(world.id())
+            .unwrap()
+            .write(&mut world);
+
+        assert!(world.get_component::<JointPalette>(mesh).is_some());
+        assert!(world.get_component::<DualQuaternionPalette>(mesh).is_none());
+    }
+}
@@ -0,0 +1,132 @@
+#![allow(dead_code)]
+use crate::{components::*, ecs::prelude::*};
+use std::collections::HashMap;
+
+pub fn build(_: &mut World, _: &mut Resources) -> Box<dyn Schedulable> {
+    // The socket target each entity was last parented to.
+    let mut attached = HashMap::<Entity, Entity>::new();
+
+    SystemBuilder::<()>::new("SocketSystem")
+        // Entities with a changed `SocketAttachment`
+        .with_query(<Read<SocketAttachment>>::query().filter(changed::<SocketAttachment>()))
+        .read_component::<SocketAttachment>()
+        .read_component::<Parent>()
+        .read_component::<LocalToParent>()
+        .build(move |commands, world, _resource, query| {
+            for (entity, attachment) in query.iter_entities(world) {
+                let parent = world.get_component::<Parent>(entity).map(|parent| parent.0);
+                if parent != Some(attachment.target) {
+                    log::trace!(
+                        "Parenting {} to the socket target {}",
+                        entity,
+                        attachment.target
+                    );
+                    commands.add_component(entity, Parent(attachment.target));
+                }
+
+                if world.get_component::<LocalToParent>(entity).is_none() {
+                    commands.add_component(entity, LocalToParent::identity());
+                }
+                attached.insert(entity, attachment.target);
+            }
+
+            // Entities whose `SocketAttachment` was removed leave the socket target, unless they
+            // have been reparented since.
+            attached.retain(|entity, target| {
+                if world.get_component::<SocketAttachment>(*entity).is_some() {
+                    return true;
+                }
+                let parent = world
+                    .get_component::<Parent>(*entity)
+                    .map(|parent| parent.0);
+                if parent == Some(*target) {
+                    log::trace!("Unparenting {} from the socket target {}", entity, target);
+                    commands.remove_component::<Parent>(*entity);
+                }
+                false
+            });
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        math::{UnitQuaternion, Vector3},
+        transform_system_bundle,
+    };
+
+    #[test]
+    fn did_attach_to_socket() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut resources = Resources::default();
+        let mut world = Universe::new().create_world();
+        let mut systems = transform_system_bundle::build(&mut world, &mut resources);
+
+        let character = *world
+            .insert(
+                (),
+                vec![(
+                    Sockets::with(&[Socket::from_translation_rotation(
+                        "hand_r",
+                        Vector3::new(0.0, 1.0, 0.0),
+                        UnitQuaternion::identity(),
+                    )]),
+                    Translation::new(1.0, 0.0, 0.0),
+                    LocalToWorld::identity(),
+                )],
+            )
+            .first()
+            .unwrap();
+        let weapon = *world
+            .insert(
+                (),
+                vec![(
+                    SocketAttachment::new(character, "hand_r"),
+                    Translation::new(0.0, 0.0, 1.0),
+                    LocalToWorld::identity(),
+                )],
+            )
+            .first()
+            .unwrap();
+
+        // The first run parents the weapon, the second builds the hierarchy and propagates.
+        for _ in 0..2 {
+            for system in systems.iter_mut() {
+                system.run(&mut world, &mut resources);
+                system
+                    .command_buffer_mut(world.id())
+                    .unwrap()
+                    .write(&mut world);
+            }
+        }
+
+        assert_eq!(
+            *world.get_component::<Parent>(weapon).unwrap(),
+            Parent(character)
+        );
+        let position = world
+            .get_component::<LocalToWorld>(weapon)
+            .unwrap()
+            .position();
+        assert!((position.coords - Vector3::new(1.0, 1.0, 1.0)).norm() < 1.0e-6);
+
+        // Removing the attachment unparents the weapon.
+        world.remove_component::<SocketAttachment>(weapon).unwrap();
+        for _ in 0..2 {
+            for system in systems.iter_mut() {
+                system.run(&mut world, &mut resources);
+                system
+                    .command_buffer_mut(world.id())
+                    .unwrap()
+                    .write(&mut world);
+            }
+        }
+
+        assert!(world.get_component::<Parent>(weapon).is_none());
+        assert!(!world
+            .get_component::<Children>(character)
+            .map_or(false, |children| children.contains(&weapon)));
+    }
+}
@@ -0,0 +1,254 @@
+#![allow(dead_code)]
+use crate::{
+    components::*,
+    ecs::{prelude::*, systems::SubWorld},
+    hierarchy_order::HierarchyOrder,
+    local_to_world_propagate_system,
+    local_transform::LocalTransformContribution,
+    local_transform_system,
+};
+
+pub fn build(_: &mut World, resources: &mut Resources) -> Box<dyn Schedulable> {
+    if !resources.contains::<HierarchyOrder>() {
+        resources.insert(HierarchyOrder::default());
+    }
+
+    let (contributions, _) = local_transform_system::contributions(resources);
+    let builder = SystemBuilder::<()>::new("StaticBakeSystem");
+    local_transform_system::declare_compose_access(&contributions, builder)
+        .write_resource::<HierarchyOrder>()
+        // Entities marked as static
+        .with_query(<Read<Static>>::query().filter(component::<LocalToWorld>()))
+        .read_component::<Static>()
+        .read_component::<Parent>()
+        .read_component::<PreviousParent>()
+        .read_component::<Children>()
+        .read_component::<Translation>()
+        .read_component::<Rotation>()
+        .read_component::<Scale>()
+        .read_component::<NonUniformScale>()
+        .read_component::<EulerRotation>()
+        .read_component::<LocalToParent>()
+        .read_component::<ScaleCompensation>()
+        .read_component::<SocketAttachment>()
+        .read_component::<Sockets>()
+        .write_component::<LocalToWorld>()
+        .build(move |commands, world, order, query| {
+            // Only bake from the top-most static entity of each static hierarchy.
+            let roots = query
+                .iter_entities(world)
+                .map(|(entity, _)| entity)
+                .filter(|entity| !has_static_ancestor(world, *entity))
+                .collect::<Vec<_>>();
+
+            for root in roots {
+                let parent_local_to_world = world
+                    .get_component::<Parent>(root)
+                    .and_then(|parent| world.get_component::<LocalToWorld>(parent.0))
+                    .map(|local_to_world| *local_to_world);
+                bake_recursive(&contributions, parent_local_to_world, world, root, commands);
+
+                // The root keeps its `PreviousParent`, so that it is removed from the `Children`
+                // of its (dynamic) parent by the `HierarchyMaintenanceSystem`.
+                commands.remove_component::<Static>(root);
+                // The hierarchy is stripped outside of the `HierarchyMaintenanceSystem`.
+                order.mark_dirty();
+            }
+        })
+}
+
+fn has_static_ancestor(world: &SubWorld, entity: Entity) -> bool {
+    let mut current = entity;
+    while let Some(parent) = world
+        .get_component::<Parent>(current)
+        .map(|parent| parent.0)
+    {
+        if world.get_component::<Static>(parent).is_some() {
+            return true;
+        }
+        current = parent;
+    }
+    false
+}
+
+fn bake_recursive(
+    contributions: &[LocalTransformContribution],
+    parent_local_to_world: Option<LocalToWorld>,
+    world: &mut SubWorld,
+    entity: Entity,
+    commands: &mut CommandBuffer,
+) {
+    let local =
+        local_transform_system::compose_entity(contributions, world, entity).or_else(|| {
+            world
+                .get_component::<LocalToParent>(entity)
+                .map(|local_to_parent| local_to_parent.0)
+        });
+
+    let local_to_world = match (parent_local_to_world, local) {
+        (Some(parent_local_to_world), Some(local)) => {
+            Some(local_to_world_propagate_system::child_local_to_world(
+                world,
+                entity,
+                &parent_local_to_world,
+                &LocalToParent(local),
+            ))
+        }
+        (None, Some(local)) if world.get_component::<Parent>(entity).is_none() => {
+            Some(LocalToWorld(local))
+        }
+        // Keep whatever `LocalToWorld` the entity already has.
+        _ => world
+            .get_component::<LocalToWorld>(entity)
+            .map(|local_to_world| *local_to_world),
+    };
+
+    log::trace!("Baking {}", entity);
+    match local_to_world {
+        Some(local_to_world) => match world.get_component_mut::<LocalToWorld>(entity) {
+            Some(mut current) => *current = local_to_world,
+            None => commands.add_component(entity, local_to_world),
+        },
+        None => log::warn!("Static entity {} does not have a LocalToWorld", entity),
+    }
+
+    let children = world
+        .get_component::<Children>(entity)
+        .map(|e| e.0.iter().cloned().collect::<Vec<_>>())
+        .unwrap_or_default();
+
+    strip::<Translation>(world, commands, entity);
+    strip::<Rotation>(world, commands, entity);
+    strip::<Scale>(world, commands, entity);
+    strip::<NonUniformScale>(world, commands, entity);
+    strip::<EulerRotation>(world, commands, entity);
+    strip::<Joint>(world, commands, entity);
+    strip::<JointValue>(world, commands, entity);
+    strip::<LocalToParent>(world, commands, entity);
+    strip::<Parent>(world, commands, entity);
+    strip::<Children>(world, commands, entity);
+    commands.add_component(entity, StaticTransform);
+
+    for child in children {
+        // The whole subtree is baked, so nothing is left to maintain between them.
+        strip::<PreviousParent>(world, commands, child);
+        strip::<Static>(world, commands, child);
+        bake_recursive(contributions, local_to_world, world, child, commands);
+    }
+}
+
+fn strip<T: Component>(world: &SubWorld, commands: &mut CommandBuffer, entity: Entity) {
+    if world.get_component::<T>(entity).is_some() {
+        commands.remove_component::<T>(entity);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        math::{Matrix4, Point3, Vector3},
+        transform_system_bundle,
+    };
+
+    #[test]
+    fn did_bake_static_hierarchy() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut resources = Resources::default();
+        let mut world = Universe::new().create_world();
+        let mut systems = transform_system_bundle::build(&mut world, &mut resources);
+
+        let building = *world
+            .insert(
+                (),
+                vec![(
+                    Static,
+                    Translation::new(10.0, 0.0, 0.0),
+                    Scale(2.0),
+                    LocalToWorld::identity(),
+                )],
+            )
+            .first()
+            .unwrap();
+        let door = *world
+            .insert(
+                (),
+                vec![(
+                    Translation::new(0.0, 1.0, 0.0),
+                    Parent(building),
+                    LocalToParent::identity(),
+                    LocalToWorld::identity(),
+                )],
+            )
+            .first()
+            .unwrap();
+
+        for _ in 0..2 {
+            for system in systems.iter_mut() {
+                system.run(&mut world, &mut resources);
+                system
+                    .command_buffer_mut(world.id())
+                    .unwrap()
+                    .write(&mut world);
+            }
+        }
+
+        for entity in [building, door].iter() {
+            assert!(world.get_component::<StaticTransform>(*entity).is_some());
+            assert!(world.get_component::<Static>(*entity).is_none());
+            assert!(world.get_component::<Translation>(*entity).is_none());
+            assert!(world.get_component::<LocalToParent>(*entity).is_none());
+            assert!(world.get_component::<Parent>(*entity).is_none());
+            assert!(world.get_component::<Children>(*entity).is_none());
+        }
+        assert!(world.get_component::<Scale>(building).is_none());
+
+        let position = world
+            .get_component::<LocalToWorld>(door)
+            .unwrap()
+            .position();
+        assert!((position.coords - Vector3::new(10.0, 2.0, 0.0)).norm() < 1.0e-6);
+    }
+
+    #[test]
+    fn baked_look_at_stays_untouched() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut resources = Resources::default();
+        let mut world = Universe::new().create_world();
+        let mut systems = transform_system_bundle::build(&mut world, &mut resources);
+
+        let turret = *world
+            .insert(
+                (),
+                vec![(
+                    Static,
+                    Translation::new(1.0, 0.0, 0.0),
+                    Rotation::identity(),
+                    LookAt::new(Point3::new(10.0, 0.0, 0.0)),
+                    LocalToWorld::identity(),
+                )],
+            )
+            .first()
+            .unwrap();
+
+        for _ in 0..3 {
+            for system in systems.iter_mut() {
+                system.run(&mut world, &mut resources);
+                system
+                    .command_buffer_mut(world.id())
+                    .unwrap()
+                    .write(&mut world);
+            }
+        }
+
+        assert!(world.get_component::<StaticTransform>(turret).is_some());
+        assert!(world.get_component::<Rotation>(turret).is_none());
+        let local_to_world = *world.get_component::<LocalToWorld>(turret).unwrap();
+        assert!(
+            (local_to_world.0 - Matrix4::new_translation(&Vector3::new(1.0, 0.0, 0.0))).norm()
+                < 1.0e-6
+        );
+    }
+}
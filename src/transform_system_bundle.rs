@@ -1,20 +1,53 @@
 use crate::{
-    ecs::prelude::*, hierarchy_maintenance_system, local_to_parent_system,
-    local_to_world_propagate_system, local_to_world_system,
+    animation_blend_system, animation_sampling_system, billboard_system, constraint_system,
+    disabled_hierarchy_system, ecs::prelude::*, euler_rotation_system,
+    hierarchy_maintenance_system, ik_system, joint_limit_system, local_to_world_propagate_system,
+    local_transform_system, look_at_system, name_index_system, retarget_system, root_motion_system,
+    skeleton_system, skinning_system, socket_system, static_bake_system,
 };
 
 pub fn build(world: &mut World, resources: &mut Resources) -> Vec<Box<dyn Schedulable>> {
-    let mut all_systems = Vec::with_capacity(5);
+    let mut all_systems = Vec::with_capacity(22);
 
+    let socket_system = socket_system::build(world, resources);
     let mut hierarchy_maintenance_systems = hierarchy_maintenance_system::build(world, resources);
-    let local_to_parent_system = local_to_parent_system::build(world, resources);
-    let local_to_world_system = local_to_world_system::build(world, resources);
+    let name_index_system = name_index_system::build(world, resources);
+    let mut disabled_hierarchy_systems = disabled_hierarchy_system::build(world, resources);
+    let animation_sampling_system = animation_sampling_system::build(world, resources);
+    let animation_blend_system = animation_blend_system::build(world, resources);
+    let retarget_system = retarget_system::build(world, resources);
+    let root_motion_system = root_motion_system::build(world, resources);
+    let euler_rotation_system = euler_rotation_system::build(world, resources);
+    let joint_limit_system = joint_limit_system::build(world, resources);
+    let local_transform_system = local_transform_system::build(world, resources);
     let local_to_world_propagate_system = local_to_world_propagate_system::build(world, resources);
+    let static_bake_system = static_bake_system::build(world, resources);
+    let constraint_system = constraint_system::build(world, resources);
+    let ik_system = ik_system::build(world, resources);
+    let look_at_system = look_at_system::build(world, resources);
+    let billboard_system = billboard_system::build(world, resources);
+    let skeleton_system = skeleton_system::build(world, resources);
+    let skinning_system = skinning_system::build(world, resources);
 
+    all_systems.push(socket_system);
     all_systems.append(&mut hierarchy_maintenance_systems);
-    all_systems.push(local_to_parent_system);
-    all_systems.push(local_to_world_system);
+    all_systems.push(name_index_system);
+    all_systems.append(&mut disabled_hierarchy_systems);
+    all_systems.push(animation_sampling_system);
+    all_systems.push(animation_blend_system);
+    all_systems.push(retarget_system);
+    all_systems.push(root_motion_system);
+    all_systems.push(euler_rotation_system);
+    all_systems.push(joint_limit_system);
+    all_systems.push(local_transform_system);
     all_systems.push(local_to_world_propagate_system);
+    all_systems.push(static_bake_system);
+    all_systems.push(constraint_system);
+    all_systems.push(ik_system);
+    all_systems.push(look_at_system);
+    all_systems.push(billboard_system);
+    all_systems.push(skeleton_system);
+    all_systems.push(skinning_system);
 
     all_systems
 }